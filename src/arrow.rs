@@ -0,0 +1,331 @@
+/*!
+
+Converting levels and players to [Apache Arrow](https://arrow.apache.org/) and writing them as Parquet, for analysis with e.g. DataFusion or Polars.
+
+Unlike the [flat rows](crate::export) of CSV exports, nothing gets flattened:
+stats, contents, aliases and leaderboards become struct columns, and tags become list columns.
+Columns are named after the serde field names, so they match the JSON Rumpus returns,
+and the dates Rumpus returns as strings become UTC timestamps with millisecond precision.
+See [level_schema] and [player_schema] for the exact schemas.
+Fields this crate doesn't know about are left out.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::arrow::ArrowExportError> {
+use std::fs::File;
+use rumpus_ce::{arrow::ParquetWriter, client::RumpusClient, query::LevelSearch};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+let search = LevelSearch::new().include_stats(true).include_records(true).limit(64)?;
+
+let mut writer = ParquetWriter::new(File::create("levels.parquet")?)?;
+writer.write_pages(client.pages(search)).await?;
+writer.finish()?;
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	io::{self, Write},
+	marker::PhantomData,
+	pin::pin,
+	sync::{Arc, OnceLock},
+};
+use ::arrow::{
+	datatypes::{DataType, Field, Fields, Schema, SchemaRef, TimeUnit},
+	error::ArrowError,
+	json::ReaderBuilder,
+	record_batch::RecordBatch,
+};
+use futures_util::stream::{Stream, StreamExt};
+use parquet::{
+	arrow::ArrowWriter,
+	basic::Compression,
+	errors::ParquetError,
+	file::properties::WriterProperties,
+};
+use serde::Serialize;
+use thiserror::Error;
+use super::{
+	client::ClientError,
+	query::LimitError,
+	types::{Level, Player},
+};
+
+#[derive(Error, Debug)]
+pub enum ArrowExportError {
+	#[error(transparent)]
+	Client(#[from] ClientError),
+	#[error(transparent)]
+	Limit(#[from] LimitError),
+	#[error(transparent)]
+	Io(#[from] io::Error),
+	#[error("couldn't convert to Arrow: {0}")]
+	Arrow(#[from] ArrowError),
+	#[error("couldn't write Parquet: {0}")]
+	Parquet(#[from] ParquetError),
+}
+
+fn timestamp() -> DataType {
+	DataType::Timestamp(TimeUnit::Millisecond, Some("+00:00".into()))
+}
+
+fn strings() -> DataType {
+	DataType::List(Arc::new(Field::new("item", DataType::Utf8, true)))
+}
+
+fn alias_fields() -> Fields {
+	Fields::from(vec![
+		Field::new("userId", DataType::Utf8, false),
+		Field::new("context", DataType::Utf8, true),
+		Field::new("alias", DataType::Utf8, true),
+		Field::new("anonymous", DataType::Boolean, true),
+	])
+}
+
+fn records() -> DataType {
+	let record = Fields::from(vec![
+		Field::new("userId", DataType::Utf8, false),
+		Field::new("alias", DataType::Struct(alias_fields()), true),
+		Field::new("value", DataType::Float32, false),
+		Field::new("createdAt", timestamp(), false),
+	]);
+	DataType::List(Arc::new(Field::new("item", DataType::Struct(record), true)))
+}
+
+/**
+The schema of a [Level]:
+
+| Column | Type |
+|-|-|
+| `_id`, `levelId`, `userId`, `avatarId`, `title`, `locale` | string |
+| `cv`, `localeId` | int32 |
+| `alias` | nullable struct of `userId`, `context`, `alias` (strings) and `anonymous` (bool) |
+| `createdAt`, `updatedAt` | timestamp (ms, UTC) |
+| `tower`, `dailyBuild` | nullable bool |
+| `towerTrial` | bool |
+| `requiredPlayers` | uint8 |
+| `creatorTime` | float32 |
+| `gameVersion` | nullable string |
+| `tags`, `tagNames` | list of strings |
+| `content` | struct of `World`, `Movement`, `Puzzles`, `Enemies`, `Hazards` (int32) |
+| `stats` | nullable struct of the [LevelStats](crate::types::LevelStats) fields: rates and time per win are float32, diamonds uint8, the rest int32 |
+| `records` | nullable struct of `HighScore` and `FastestTime`, lists of structs of `userId`, `alias`, `value` (float32) and `createdAt` |
+| `interactions` | nullable struct of `bookmarked`, `liked` and `favorited` (bools) |
+
+`stats.HiddenGem` is only there with the `undocumented` feature.
+*/
+pub fn level_schema() -> SchemaRef {
+	static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+	SCHEMA.get_or_init(|| {
+		let content = Fields::from(vec![
+			Field::new("World", DataType::Int32, false),
+			Field::new("Movement", DataType::Int32, false),
+			Field::new("Puzzles", DataType::Int32, false),
+			Field::new("Enemies", DataType::Int32, false),
+			Field::new("Hazards", DataType::Int32, false),
+		]);
+		let stats = Fields::from(vec![
+			Field::new("Attempts", DataType::Int32, false),
+			Field::new("Favorites", DataType::Int32, false),
+			Field::new("Likes", DataType::Int32, false),
+			Field::new("PerkPoints", DataType::Int32, true),
+			Field::new("PlayTime", DataType::Int32, false),
+			Field::new("Players", DataType::Int32, false),
+			Field::new("ReplayValue", DataType::Int32, false),
+			Field::new("ClearRate", DataType::Float32, false),
+			Field::new("Diamonds", DataType::UInt8, false),
+			Field::new("Successes", DataType::Int32, false),
+			Field::new("TimePerWin", DataType::Float32, false),
+			Field::new("ExposureBucks", DataType::Int32, false),
+			Field::new("FailureRate", DataType::Float32, false),
+			#[cfg(feature="undocumented")]
+			Field::new("HiddenGem", DataType::Int32, false),
+		]);
+		let records = Fields::from(vec![
+			Field::new("HighScore", records(), false),
+			Field::new("FastestTime", records(), false),
+		]);
+		let interactions = Fields::from(vec![
+			Field::new("bookmarked", DataType::Boolean, false),
+			Field::new("liked", DataType::Boolean, false),
+			Field::new("favorited", DataType::Boolean, false),
+		]);
+		
+		Arc::new(Schema::new(vec![
+			Field::new("_id", DataType::Utf8, false),
+			Field::new("cv", DataType::Int32, false),
+			Field::new("levelId", DataType::Utf8, false),
+			Field::new("userId", DataType::Utf8, false),
+			Field::new("alias", DataType::Struct(alias_fields()), true),
+			Field::new("avatarId", DataType::Utf8, false),
+			Field::new("title", DataType::Utf8, false),
+			Field::new("localeId", DataType::Int32, false),
+			Field::new("locale", DataType::Utf8, false),
+			Field::new("createdAt", timestamp(), false),
+			Field::new("updatedAt", timestamp(), false),
+			Field::new("tower", DataType::Boolean, true),
+			Field::new("dailyBuild", DataType::Boolean, true),
+			Field::new("towerTrial", DataType::Boolean, false),
+			Field::new("requiredPlayers", DataType::UInt8, false),
+			Field::new("creatorTime", DataType::Float32, false),
+			Field::new("gameVersion", DataType::Utf8, true),
+			Field::new("tags", strings(), false),
+			Field::new("tagNames", strings(), false),
+			Field::new("content", DataType::Struct(content), false),
+			Field::new("stats", DataType::Struct(stats), true),
+			Field::new("records", DataType::Struct(records), true),
+			Field::new("interactions", DataType::Struct(interactions), true),
+		]))
+	}).clone()
+}
+
+/**
+The schema of a [Player]:
+
+| Column | Type |
+|-|-|
+| `_id`, `userId` | string |
+| `alias` | nullable struct of `userId`, `context`, `alias` (strings) and `anonymous` (bool) |
+| `createdAt`, `updatedAt` | timestamp (ms, UTC) |
+| `interactions` | nullable struct of `following` (bool) |
+| `stats` | struct of the [PlayerStats](crate::types::PlayerStats) fields: `CampaignProg` is uint8, the rest int32, `DBComp` and `AchPoints` are nullable |
+*/
+pub fn player_schema() -> SchemaRef {
+	static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+	SCHEMA.get_or_init(|| {
+		let stats = Fields::from(vec![
+			Field::new("Subscribers", DataType::Int32, false),
+			Field::new("Published", DataType::Int32, false),
+			Field::new("Plays", DataType::Int32, false),
+			Field::new("PlayTime", DataType::Int32, false),
+			Field::new("Crowns", DataType::Int32, false),
+			Field::new("Shoes", DataType::Int32, false),
+			Field::new("LevelsPlayed", DataType::Int32, false),
+			Field::new("Wins", DataType::Int32, false),
+			Field::new("Fails", DataType::Int32, false),
+			Field::new("NumFollowing", DataType::Int32, false),
+			Field::new("DBComp", DataType::Int32, true),
+			Field::new("ChalWins", DataType::Int32, false),
+			Field::new("TimeTrophies", DataType::Int32, false),
+			Field::new("FaveGen", DataType::Int32, false),
+			Field::new("LikeGen", DataType::Int32, false),
+			Field::new("BucksTipped", DataType::Int32, false),
+			Field::new("TipsGotten", DataType::Int32, false),
+			Field::new("AchPoints", DataType::Int32, true),
+			Field::new("CampaignProg", DataType::UInt8, false),
+		]);
+		let interactions = Fields::from(vec![
+			Field::new("following", DataType::Boolean, false),
+		]);
+		
+		Arc::new(Schema::new(vec![
+			Field::new("_id", DataType::Utf8, false),
+			Field::new("userId", DataType::Utf8, false),
+			Field::new("alias", DataType::Struct(alias_fields()), true),
+			Field::new("createdAt", timestamp(), false),
+			Field::new("updatedAt", timestamp(), false),
+			Field::new("interactions", DataType::Struct(interactions), true),
+			Field::new("stats", DataType::Struct(stats), false),
+		]))
+	}).clone()
+}
+
+///Something that can be converted to Arrow
+pub trait ToArrow: Serialize {
+	///The schema of the record batches
+	fn schema() -> SchemaRef;
+}
+
+impl ToArrow for Level {
+	fn schema() -> SchemaRef {
+		level_schema()
+	}
+}
+
+impl ToArrow for Player {
+	fn schema() -> SchemaRef {
+		player_schema()
+	}
+}
+
+///Converts a batch of levels or players into a [RecordBatch] with their [schema](ToArrow::schema)
+pub fn record_batch<T: ToArrow>(items: &[T]) -> Result<RecordBatch, ArrowExportError> {
+	let schema = T::schema();
+	let mut decoder = ReaderBuilder::new(schema.clone())
+		.with_batch_size(items.len().max(1))
+		.build_decoder()?;
+	decoder.serialize(items)?;
+	let batch = decoder.flush()?
+		.unwrap_or_else(|| RecordBatch::new_empty(schema));
+	Ok(batch)
+}
+
+///Writes levels or players to a Parquet file, see the [module docs](self)
+pub struct ParquetWriter<W: Write + Send, T> {
+	writer: ArrowWriter<W>,
+	written: usize,
+	item: PhantomData<fn(&T)>,
+}
+
+impl<W: Write + Send, T: ToArrow> ParquetWriter<W, T> {
+	///Writes Snappy compressed Parquet to `writer`
+	pub fn new(writer: W) -> Result<Self, ArrowExportError> {
+		let properties = WriterProperties::builder()
+			.set_compression(Compression::SNAPPY)
+			.build();
+		Self::with_properties(writer, properties)
+	}
+	
+	pub fn with_properties(writer: W, properties: WriterProperties) -> Result<Self, ArrowExportError> {
+		Ok(Self {
+			writer: ArrowWriter::try_new(writer, T::schema(), Some(properties))?,
+			written: 0,
+			item: PhantomData,
+		})
+	}
+	
+	///How many rows have been written
+	pub fn written(&self) -> usize {
+		self.written
+	}
+	
+	pub fn write(&mut self, items: &[T]) -> Result<(), ArrowExportError> {
+		if items.is_empty() {
+			return Ok(());
+		}
+		self.writer.write(&record_batch(items)?)?;
+		self.written += items.len();
+		Ok(())
+	}
+	
+	/**
+	Writes every page of a search as it arrives, returning how many rows were written.
+	
+	# Errors
+	Stops at the first page that failed, after writing all pages before it.
+	*/
+	pub async fn write_pages(&mut self, pages: impl Stream<Item = Result<Vec<T>, ClientError>>) -> Result<usize, ArrowExportError> {
+		let mut pages = pin!(pages);
+		let start = self.written;
+		while let Some(page) = pages.next().await {
+			self.write(&page?)?;
+		}
+		Ok(self.written - start)
+	}
+	
+	///Like [Self::write_pages], but for the pages of a [blocking client](crate::blocking)
+	pub fn write_pages_blocking(&mut self, pages: impl IntoIterator<Item = Result<Vec<T>, ClientError>>) -> Result<usize, ArrowExportError> {
+		let start = self.written;
+		for page in pages {
+			self.write(&page?)?;
+		}
+		Ok(self.written - start)
+	}
+	
+	///Writes the Parquet footer, and returns the writer
+	pub fn finish(self) -> Result<W, ArrowExportError> {
+		Ok(self.writer.into_inner()?)
+	}
+}
@@ -0,0 +1,502 @@
+/*!
+
+Command-line access to Rumpus CE, for the ad-hoc searches and lookups you'd otherwise curl for.
+
+The delegation key is read from `--key`, or else from where [KeySource::default] looks for it.
+Run `rumpus help` to see everything it can do.
+
+*/
+
+use std::process::ExitCode;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use rumpus_ce::{
+	blocking::BlockingRumpusClient,
+	client::ClientError,
+	environment::Environment,
+	key::KeySource,
+	query::*,
+	types::*,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "rumpus", version, about = "Search and look up Levelhead levels and players through Rumpus CE")]
+struct Cli {
+	///The delegation key to use, instead of the one in RUMPUS_DELEGATION_KEY or the config directory
+	#[arg(long, global = true)]
+	key: Option<String>,
+	///The API to send requests to: production, beta, or its url
+	#[arg(long, default_value = "production", global = true)]
+	url: Environment,
+	///Use the beta API instead, overrides --url
+	#[arg(long, global = true)]
+	beta: bool,
+	///How to print the results
+	#[arg(long, short, value_enum, default_value_t = Format::Table, global = true)]
+	format: Format,
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+	///Aligned columns with the most interesting fields
+	Table,
+	///Everything, as a single JSON value
+	Json,
+	///Everything, one JSON object per line
+	JsonLines,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+	///Information about the delegation key
+	KeyInfo,
+	///Search for levels
+	Levels {
+		#[command(flatten)]
+		search: LevelArgs,
+		///Page through all results instead of only returning the first page
+		#[arg(long)]
+		all: bool,
+	},
+	///Search for players
+	Players {
+		#[command(flatten)]
+		search: PlayerArgs,
+		///Page through all results instead of only returning the first page
+		#[arg(long)]
+		all: bool,
+	},
+	///Look up levels by their code or lvlhd.co link, including their stats
+	Level {
+		#[arg(required = true)]
+		codes: Vec<String>,
+	},
+	///Look up players by their user id or lvlhd.co link, including their alias
+	Player {
+		#[arg(required = true)]
+		user_ids: Vec<String>,
+	},
+}
+
+///The parameters of [LevelSearch]. Boolean flags without a value mean true.
+#[derive(Args, Debug)]
+struct LevelArgs {
+	///What to sort on, in the API format: e.g. `-createdAt` for oldest first
+	#[arg(long, allow_hyphen_values = true)]
+	sort: Option<LevelSearchSort>,
+	#[arg(long)]
+	limit: Option<u8>,
+	#[arg(long, value_delimiter = ',')]
+	user_ids: Vec<String>,
+	#[arg(long, value_delimiter = ',')]
+	level_ids: Vec<String>,
+	#[arg(long)]
+	tags: Option<String>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	tower: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	marketing: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	daily_build: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_stats: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_records: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_aliases: Option<bool>,
+	#[arg(long)]
+	min_play_time: Option<Stat>,
+	#[arg(long)]
+	max_play_time: Option<Stat>,
+	#[arg(long)]
+	min_exposure_bucks: Option<Stat>,
+	#[arg(long)]
+	max_exposure_bucks: Option<Stat>,
+	#[arg(long)]
+	min_replay_value: Option<Stat>,
+	#[arg(long)]
+	max_replay_value: Option<Stat>,
+	#[arg(long)]
+	min_hidden_gem: Option<Stat>,
+	#[arg(long)]
+	max_hidden_gem: Option<Stat>,
+	#[arg(long)]
+	diamonds: Option<u8>,
+	#[arg(long)]
+	min_diamonds: Option<Stat>,
+	#[arg(long)]
+	max_diamonds: Option<Stat>,
+	#[arg(long)]
+	min_seconds_ago: Option<Stat>,
+	#[arg(long)]
+	max_seconds_ago: Option<Stat>,
+	#[arg(long)]
+	tiebreaker_item_id: Option<String>,
+	#[arg(long)]
+	min_created_at: Option<String>,
+	#[arg(long)]
+	max_created_at: Option<String>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_my_interactions: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_beta: Option<bool>,
+}
+
+impl LevelArgs {
+	fn search(self) -> anyhow::Result<LevelSearch> {
+		let mut search = LevelSearch {
+			sort: self.sort,
+			tags: self.tags,
+			tower: self.tower,
+			marketing: self.marketing,
+			daily_build: self.daily_build,
+			include_stats: self.include_stats,
+			include_records: self.include_records,
+			include_aliases: self.include_aliases,
+			min_play_time: self.min_play_time,
+			max_play_time: self.max_play_time,
+			min_exposure_bucks: self.min_exposure_bucks,
+			max_exposure_bucks: self.max_exposure_bucks,
+			min_replay_value: self.min_replay_value,
+			max_replay_value: self.max_replay_value,
+			min_hidden_gem: self.min_hidden_gem,
+			max_hidden_gem: self.max_hidden_gem,
+			diamonds: self.diamonds,
+			min_diamonds: self.min_diamonds,
+			max_diamonds: self.max_diamonds,
+			min_seconds_ago: self.min_seconds_ago,
+			max_seconds_ago: self.max_seconds_ago,
+			tiebreaker_item_id: self.tiebreaker_item_id,
+			min_created_at: self.min_created_at,
+			max_created_at: self.max_created_at,
+			include_my_interactions: self.include_my_interactions,
+			include_beta: self.include_beta,
+			..LevelSearch::new()
+		};
+		if let Some(limit) = self.limit {
+			search = search.limit(limit)?;
+		}
+		if !self.user_ids.is_empty() {
+			search = search.user_ids(self.user_ids)?;
+		}
+		if !self.level_ids.is_empty() {
+			search = search.level_ids(self.level_ids.iter().map(|code| level_code(code)).collect::<Vec<_>>())?;
+		}
+		Ok(search)
+	}
+}
+
+///The parameters of [PlayerSearch]. Boolean flags without a value mean true.
+#[derive(Args, Debug)]
+struct PlayerArgs {
+	///What to sort on, in the API format: e.g. `-createdAt` for oldest first
+	#[arg(long, allow_hyphen_values = true)]
+	sort: Option<PlayerSearchSort>,
+	#[arg(long)]
+	limit: Option<u8>,
+	#[arg(long, value_delimiter = ',')]
+	user_ids: Vec<String>,
+	#[arg(long)]
+	min_subscribers: Option<Stat>,
+	#[arg(long)]
+	max_subscribers: Option<Stat>,
+	#[arg(long)]
+	min_play_time: Option<Stat>,
+	#[arg(long)]
+	max_play_time: Option<Stat>,
+	#[arg(long)]
+	min_created_at: Option<String>,
+	#[arg(long)]
+	max_created_at: Option<String>,
+	#[arg(long)]
+	min_updated_at: Option<String>,
+	#[arg(long)]
+	max_updated_at: Option<String>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_aliases: Option<bool>,
+	#[arg(long, num_args = 0..=1, default_missing_value = "true")]
+	include_my_interactions: Option<bool>,
+	#[arg(long)]
+	tiebreaker_item_id: Option<String>,
+}
+
+impl PlayerArgs {
+	fn search(self) -> anyhow::Result<PlayerSearch> {
+		let mut search = PlayerSearch {
+			sort: self.sort,
+			min_subscribers: self.min_subscribers,
+			max_subscribers: self.max_subscribers,
+			min_play_time: self.min_play_time,
+			max_play_time: self.max_play_time,
+			min_created_at: self.min_created_at,
+			max_created_at: self.max_created_at,
+			min_updated_at: self.min_updated_at,
+			max_updated_at: self.max_updated_at,
+			include_aliases: self.include_aliases,
+			include_my_interactions: self.include_my_interactions,
+			tiebreaker_item_id: self.tiebreaker_item_id,
+			..PlayerSearch::new()
+		};
+		if let Some(limit) = self.limit {
+			search = search.limit(limit)?;
+		}
+		if !self.user_ids.is_empty() {
+			search = search.user_ids(self.user_ids.iter().map(|id| user_id(id)).collect::<Vec<_>>())?;
+		}
+		Ok(search)
+	}
+}
+
+///Strips the lvlhd.co link around a code, if there is one
+fn strip_link(input: &str, marker: char) -> &str {
+	let input = input.trim();
+	let path = input.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+	let path = path.strip_prefix("https://").or_else(|| path.strip_prefix("http://")).unwrap_or(path);
+	let path = path.strip_prefix("www.").unwrap_or(path);
+	match path.strip_prefix("lvlhd.co/") {
+		Some(code) => code.trim_start_matches(marker),
+		None => input,
+	}
+}
+
+///A level code from either the code itself or a `lvlhd.co/+code` link
+fn level_code(input: &str) -> String {
+	strip_link(input, '+').to_owned()
+}
+
+///A user id from either the id itself or a `lvlhd.co/@id` link
+fn user_id(input: &str) -> String {
+	strip_link(input, '@').to_owned()
+}
+
+///Something that can be printed as a table row
+trait Row {
+	const HEADERS: &'static [&'static str];
+	
+	fn cells(&self) -> Vec<String>;
+}
+
+fn alias_name(alias: &Option<Alias>) -> String {
+	alias.as_ref().and_then(|a| a.alias.clone()).unwrap_or_default()
+}
+
+impl Row for Level {
+	const HEADERS: &'static [&'static str] = &["CODE", "TITLE", "CREATOR", "CREATED", "PLAYERS", "LIKES", "FAVORITES", "CLEAR RATE", "DIAMONDS"];
+	
+	fn cells(&self) -> Vec<String> {
+		let creator = match &self.alias {
+			Some(Alias { alias: Some(alias), .. }) => format!("{alias} ({})", self.user_id),
+			_ => self.user_id.clone(),
+		};
+		let mut cells = vec![self.level_id.clone(), self.title.clone(), creator, self.created_at.clone()];
+		match &self.stats {
+			Some(stats) => cells.extend([
+				stats.players.to_string(),
+				stats.likes.to_string(),
+				stats.favorites.to_string(),
+				format!("{:.1}%", stats.clear_rate.get() * 100.0),
+				stats.diamonds.to_string(),
+			]),
+			None => cells.extend(["-"; 5].map(String::from)),
+		}
+		cells
+	}
+}
+
+impl Row for Player {
+	const HEADERS: &'static [&'static str] = &["USER", "ALIAS", "CREATED", "SUBSCRIBERS", "PUBLISHED", "PLAYS", "CROWNS", "SHOES"];
+	
+	fn cells(&self) -> Vec<String> {
+		vec![
+			self.user_id.clone(),
+			alias_name(&self.alias),
+			self.created_at.clone(),
+			self.stats.subscribers.to_string(),
+			self.stats.published.to_string(),
+			self.stats.plays.to_string(),
+			self.stats.crowns.to_string(),
+			self.stats.shoes.to_string(),
+		]
+	}
+}
+
+impl Row for DelegationKeyInfo {
+	const HEADERS: &'static [&'static str] = &["USER", "PASS", "PERMISSIONS"];
+	
+	fn cells(&self) -> Vec<String> {
+		let mut permissions = self.permissions.iter().map(ToString::to_string).collect::<Vec<_>>();
+		permissions.sort_unstable();
+		vec![self.user_id.clone(), self.pass_id.clone(), permissions.join(",")]
+	}
+}
+
+fn print_table<T: Row>(items: &[T]) {
+	let rows = items.iter().map(Row::cells).collect::<Vec<_>>();
+	let mut widths = T::HEADERS.iter().map(|h| h.chars().count()).collect::<Vec<_>>();
+	for row in &rows {
+		for (width, cell) in widths.iter_mut().zip(row) {
+			*width = (*width).max(cell.chars().count());
+		}
+	}
+	
+	let headers = T::HEADERS.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+	for row in std::iter::once(&headers).chain(&rows) {
+		let line = row.iter()
+			.zip(&widths)
+			.map(|(cell, width)| format!("{cell:width$}"))
+			.collect::<Vec<_>>()
+			.join("  ");
+		println!("{}", line.trim_end());
+	}
+}
+
+///Prints every page as it arrives for JSON Lines, or everything at once for the other formats
+fn print_pages<T: Row + Serialize>(pages: impl Iterator<Item = Result<Vec<T>, ClientError>>, format: Format) -> anyhow::Result<()> {
+	let mut all = Vec::new();
+	for page in pages {
+		let page = page?;
+		if format == Format::JsonLines {
+			for item in &page {
+				println!("{}", serde_json::to_string(item)?);
+			}
+		} else {
+			all.extend(page);
+		}
+	}
+	
+	match format {
+		Format::Table => print_table(&all),
+		Format::Json => println!("{}", serde_json::to_string_pretty(&all)?),
+		Format::JsonLines => {},
+	}
+	Ok(())
+}
+
+///Prints single lookups, returning whether all of them were found
+fn print_lookups<T: Row + Serialize>(found: Vec<Option<T>>, format: Format) -> anyhow::Result<bool> {
+	let all_found = found.iter().all(Option::is_some);
+	let found = found.into_iter().flatten().collect::<Vec<_>>();
+	match (format, found.as_slice()) {
+		(Format::Json, [single]) => println!("{}", serde_json::to_string_pretty(single)?),
+		_ => print_pages(std::iter::once(Ok(found)), format)?,
+	}
+	Ok(all_found)
+}
+
+fn run(cli: Cli) -> anyhow::Result<ExitCode> {
+	let key = cli.key.map(KeySource::key).unwrap_or_default().load()?;
+	let environment = if cli.beta { Environment::Beta } else { cli.url };
+	let client = BlockingRumpusClient::with_key_environment(key.expose(), &environment)?;
+	let format = cli.format;
+	
+	match cli.command {
+		Command::KeyInfo => {
+			let info = client.key_info()?;
+			print_lookups(vec![Some(info)], format)?;
+		},
+		Command::Levels { search, all } => {
+			let search = search.search()?;
+			if all {
+				let search = match search.limit {
+					Some(_) => search,
+					None => search.limit(LevelSearch::MAX_LIMIT as u8)?,
+				};
+				print_pages(client.pages(search), format)?;
+			} else {
+				print_pages(std::iter::once(client.levels(search)), format)?;
+			}
+		},
+		Command::Players { search, all } => {
+			let search = search.search()?;
+			if all {
+				let search = match search.limit {
+					Some(_) => search,
+					None => search.limit(PlayerSearch::MAX_LIMIT as u8)?,
+				};
+				print_pages(client.pages(search), format)?;
+			} else {
+				print_pages(std::iter::once(client.players(search)), format)?;
+			}
+		},
+		Command::Level { codes } => {
+			let mut found = Vec::new();
+			for code in codes.iter().map(|c| level_code(c)) {
+				let level = client.level(&code)?;
+				if level.is_none() {
+					eprintln!("no level with code {code}");
+				}
+				found.push(level);
+			}
+			if !print_lookups(found, format)? {
+				return Ok(ExitCode::FAILURE);
+			}
+		},
+		Command::Player { user_ids } => {
+			let mut found = Vec::new();
+			for id in user_ids.iter().map(|id| user_id(id)) {
+				let player = client.player(&id)?;
+				if player.is_none() {
+					eprintln!("no player with user id {id}");
+				}
+				found.push(player);
+			}
+			if !print_lookups(found, format)? {
+				return Ok(ExitCode::FAILURE);
+			}
+		},
+	}
+	
+	Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+	match run(Cli::parse()) {
+		Ok(code) => code,
+		Err(error) => {
+			eprintln!("error: {error:#}");
+			ExitCode::FAILURE
+		},
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn links() {
+		assert_eq!(level_code("abcdefg"), "abcdefg");
+		assert_eq!(level_code("https://lvlhd.co/+abcdefg"), "abcdefg");
+		assert_eq!(level_code("lvlhd.co/+abcdefg/"), "abcdefg");
+		assert_eq!(user_id("https://www.lvlhd.co/@bscotch119?ref=x"), "bscotch119");
+		assert_eq!(user_id(" bscotch119 "), "bscotch119");
+	}
+	
+	#[test]
+	fn arguments() -> anyhow::Result<()> {
+		let cli = Cli::try_parse_from(["rumpus", "levels", "--sort", "-createdAt", "--limit", "5", "--tower", "--include-stats=false", "--level-ids", "lvlhd.co/+abc,def"])?;
+		let Command::Levels { search, all: false } = cli.command else {
+			panic!("parsed as {:?}", cli.command);
+		};
+		let search = search.search()?;
+		assert_eq!(search.to_string(), "levelIds=abc,def&sort=-createdAt&limit=5&tower=true&includeStats=false");
+		
+		let cli = Cli::try_parse_from(["rumpus", "players", "--all", "--limit", "65"])?;
+		let Command::Players { search, .. } = cli.command else {
+			panic!("parsed as {:?}", cli.command);
+		};
+		assert!(search.search().is_err());
+		
+		let cli = Cli::try_parse_from(["rumpus", "players", "--sort", "-createdAt"])?;
+		let Command::Players { search, .. } = cli.command else {
+			panic!("parsed as {:?}", cli.command);
+		};
+		assert_eq!(search.search()?.to_string(), "sort=-createdAt");
+		
+		Ok(())
+	}
+}
@@ -0,0 +1,207 @@
+/*!
+
+A synchronous version of the [client](crate::client), for when you don't want to deal with async.
+
+[BlockingRumpusClient] has the same methods as [RumpusClient], but blocks until they're done,
+and pages through searches with an [Iterator] instead of a stream.
+It drives the async client on its own single-threaded tokio runtime,
+so it can't be used from within an async context: use the async client there.
+
+```no_run
+# fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use rumpus_ce::{blocking::BlockingRumpusClient, query::*};
+
+let client = BlockingRumpusClient::with_key("your-delegation-key")?;
+
+for page in client.pages(PlayerSearch::new().newest_first().limit(64)?) {
+	for player in page? {
+		println!("{}", player.user_id);
+	}
+}
+# Ok(())
+# }
+```
+
+*/
+
+use std::{pin::Pin, sync::Arc};
+use futures_util::stream::{Stream, StreamExt};
+use restson::RestPath;
+use serde::de::DeserializeOwned;
+use tokio::runtime::{Builder, Runtime};
+use super::{
+	cache::ResponseCache,
+	client::{ClientError, PagedSearch, RumpusClient},
+	environment::Environment,
+	query::{AliasSearch, LevelSearch, PlayerSearch},
+	records::PlayerRecords,
+	transport::Transport,
+	types::*,
+	unknown::{FieldPolicy, UnknownFields},
+};
+
+///A blocking client for Rumpus CE, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct BlockingRumpusClient {
+	client: RumpusClient,
+	runtime: Arc<Runtime>,
+}
+
+impl BlockingRumpusClient {
+	/**
+	Blocks on an async client.
+	
+	# Errors
+	If the runtime couldn't be started.
+	*/
+	pub fn from_async(client: RumpusClient) -> Result<Self, ClientError> {
+		let runtime = Builder::new_current_thread()
+			.enable_all()
+			.build()
+			.map_err(ClientError::Runtime)?;
+		Ok(Self {
+			client,
+			runtime: Arc::new(runtime),
+		})
+	}
+	
+	/**
+	Sends requests through the given transport.
+	
+	# Errors
+	If the runtime couldn't be started.
+	*/
+	pub fn new(transport: impl Transport + 'static) -> Result<Self, ClientError> {
+		Self::from_async(RumpusClient::new(transport))
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport](crate::transport::RestsonTransport) with a given delegation key and url/endpoint.
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If `url` is not a valid URL
+	- If the runtime couldn't be started
+	*/
+	pub fn with_key_url(key: impl AsRef<str>, url: impl AsRef<str>) -> Result<Self, ClientError> {
+		Self::from_async(RumpusClient::with_key_url(key, url)?)
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport](crate::transport::RestsonTransport) with a given delegation key, talking to `environment`.
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If the runtime couldn't be started
+	*/
+	pub fn with_key_environment(key: impl AsRef<str>, environment: &Environment) -> Result<Self, ClientError> {
+		Self::from_async(RumpusClient::with_key_environment(key, environment)?)
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport](crate::transport::RestsonTransport) with a given delegation key and using [the default API url/endpoint](super::API_URL).
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If the runtime couldn't be started
+	*/
+	pub fn with_key(key: impl AsRef<str>) -> Result<Self, ClientError> {
+		Self::from_async(RumpusClient::with_key(key)?)
+	}
+	
+	///What to do with fields in responses that this crate doesn't know about
+	pub fn field_policy(mut self, policy: FieldPolicy) -> Self {
+		self.client = self.client.field_policy(policy);
+		self
+	}
+	
+	///Serve responses from this cache while they're fresh, and cache new ones in it
+	pub fn cache(mut self, cache: ResponseCache) -> Self {
+		self.client = self.client.cache(cache);
+		self
+	}
+	
+	///Store every fetched level, player and alias in this cache, and serve lookups from it, see [crate::sqlite_cache]
+	#[cfg(feature="sqlite-cache")]
+	pub fn sqlite_cache(mut self, cache: crate::sqlite_cache::SqliteCache) -> Self {
+		self.client = self.client.sqlite_cache(cache);
+		self
+	}
+	
+	///The async client this blocks on
+	pub fn as_async(&self) -> &RumpusClient {
+		&self.client
+	}
+	
+	///See [RumpusClient::get]
+	pub fn get<U, T>(&self, params: U) -> Result<T, ClientError>
+	where
+		T: RestPath<U> + DeserializeOwned + UnknownFields,
+	{
+		self.runtime.block_on(self.client.get(params))
+	}
+	
+	///Information about the delegation key this client uses
+	pub fn key_info(&self) -> Result<DelegationKeyInfo, ClientError> {
+		self.runtime.block_on(self.client.key_info())
+	}
+	
+	///A single page of players
+	pub fn players(&self, search: PlayerSearch) -> Result<Vec<Player>, ClientError> {
+		self.runtime.block_on(self.client.players(search))
+	}
+	
+	///A single page of levels
+	pub fn levels(&self, search: LevelSearch) -> Result<Vec<Level>, ClientError> {
+		self.runtime.block_on(self.client.levels(search))
+	}
+	
+	pub fn aliases(&self, search: AliasSearch) -> Result<Vec<Alias>, ClientError> {
+		self.runtime.block_on(self.client.aliases(search))
+	}
+	
+	///Looks up a single player, including their alias
+	pub fn player(&self, user_id: impl Into<String>) -> Result<Option<Player>, ClientError> {
+		self.runtime.block_on(self.client.player(user_id))
+	}
+	
+	///Looks up a single level, including its stats
+	pub fn level(&self, level_id: impl Into<String>) -> Result<Option<Level>, ClientError> {
+		self.runtime.block_on(self.client.level(level_id))
+	}
+	
+	pub fn alias(&self, user_id: impl Into<String>) -> Result<Option<Alias>, ClientError> {
+		self.runtime.block_on(self.client.alias(user_id))
+	}
+	
+	///The leaderboard records a player holds on the levels a search finds, see [RumpusClient::player_records]
+	pub fn player_records(&self, user_id: impl Into<String>, search: LevelSearch) -> Result<PlayerRecords, ClientError> {
+		self.runtime.block_on(self.client.player_records(user_id, search))
+	}
+	
+	///Pages through all results of a search, see [RumpusClient::pages]
+	pub fn pages<S>(&self, search: S) -> Pages<'_, S::Item>
+	where
+		S: PagedSearch + 'static,
+		Rumpus<Vec<S::Item>>: RestPath<S> + DeserializeOwned + UnknownFields,
+	{
+		Pages {
+			stream: Box::pin(self.client.pages(search)),
+			runtime: &self.runtime,
+		}
+	}
+}
+
+///An iterator over the pages of a search, see [BlockingRumpusClient::pages]
+pub struct Pages<'c, T> {
+	stream: Pin<Box<dyn Stream<Item = Result<Vec<T>, ClientError>> + Send + 'c>>,
+	runtime: &'c Runtime,
+}
+
+impl<T> Iterator for Pages<'_, T> {
+	type Item = Result<Vec<T>, ClientError>;
+	
+	fn next(&mut self) -> Option<Self::Item> {
+		self.runtime.block_on(self.stream.next())
+	}
+}
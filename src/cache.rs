@@ -0,0 +1,381 @@
+/*!
+
+An in-memory cache for the responses the [client](crate::client) receives.
+
+Responses are cached per endpoint and query, with the query parameters sorted so their order doesn't matter,
+and per [scope](crate::transport::Transport::scope): the API url and delegation key the response was received with.
+So clients with different keys or environments can share a cache without getting each other's responses,
+like who a key belongs to or the interactions of its account.
+How long a response stays fresh depends on the [Endpoint] it came from:
+key info barely changes, while level stats change all the time.
+The cache is bounded in both the amount of responses and their total size, evicting the least recently used ones first.
+
+[ResponseCache] is cheap to clone and all clones share the same cache,
+so keep one around to invalidate responses after something changed them:
+
+```no_run
+# fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use std::time::Duration;
+use rumpus_ce::{cache::{Endpoint, ResponseCache}, client::RumpusClient};
+
+let cache = ResponseCache::new()
+	.ttl(Endpoint::Levels, Duration::from_secs(30));
+let client = RumpusClient::with_key("your-delegation-key")?
+	.cache(cache.clone());
+
+//After liking a level, its like count is out of date
+cache.invalidate(Endpoint::Levels);
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard},
+	time::{Duration, Instant},
+};
+
+///The endpoints responses can come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+	KeyInfo,
+	Players,
+	Levels,
+	Aliases,
+	///Any endpoint this crate doesn't know about
+	Other,
+}
+
+impl Endpoint {
+	///All endpoints
+	pub const ALL: &'static [Self] = &[
+		Self::KeyInfo,
+		Self::Players,
+		Self::Levels,
+		Self::Aliases,
+		Self::Other,
+	];
+	
+	///The endpoint a request path (relative to the API url) goes to
+	pub fn of(path: &str) -> Self {
+		match path.split('?').next().unwrap_or_default().trim_end_matches('/') {
+			"delegation/keys/@this" => Self::KeyInfo,
+			"levelhead/players" => Self::Players,
+			"levelhead/levels" => Self::Levels,
+			"levelhead/aliases" => Self::Aliases,
+			_ => Self::Other,
+		}
+	}
+	
+	///A short name for the endpoint, e.g. `key_info` or `levels`
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::KeyInfo => "key_info",
+			Self::Players => "players",
+			Self::Levels => "levels",
+			Self::Aliases => "aliases",
+			Self::Other => "other",
+		}
+	}
+	
+	///How long responses from this endpoint stay fresh by default
+	pub fn default_ttl(self) -> Duration {
+		match self {
+			Self::KeyInfo => Duration::from_secs(60 * 60),
+			Self::Aliases => Duration::from_secs(15 * 60),
+			Self::Players => Duration::from_secs(5 * 60),
+			Self::Levels => Duration::from_secs(60),
+			Self::Other => Duration::ZERO,
+		}
+	}
+}
+
+/**
+The key a request path is cached under.
+
+The query parameters get sorted, so `a=1&b=2` and `b=2&a=1` are cached as the same response.
+*/
+pub fn cache_key(path: &str) -> String {
+	match path.split_once('?') {
+		Some((endpoint, query)) => {
+			let mut parameters = query.split('&').filter(|p| !p.is_empty()).collect::<Vec<_>>();
+			parameters.sort_unstable();
+			format!("{endpoint}?{}", parameters.join("&"))
+		},
+		None => path.to_owned(),
+	}
+}
+
+#[derive(Debug)]
+struct Entry {
+	endpoint: Endpoint,
+	body: Arc<str>,
+	expires: Instant,
+	last_used: u64,
+}
+
+#[derive(Debug)]
+struct Inner {
+	ttls: HashMap<Endpoint, Duration>,
+	max_entries: usize,
+	max_bytes: usize,
+	///By scope and cache key
+	entries: HashMap<(String, String), Entry>,
+	bytes: usize,
+	///Incremented on every use, to know which entry was used the longest ago
+	clock: u64,
+}
+
+///A cache for response bodies, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+	inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for ResponseCache {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl ResponseCache {
+	pub const DEFAULT_MAX_ENTRIES: usize = 1024;
+	pub const DEFAULT_MAX_BYTES: usize = 64 * 1024 * 1024;
+	
+	///A cache using the [default TTLs](Endpoint::default_ttl) and size bounds
+	pub fn new() -> Self {
+		Self {
+			inner: Arc::new(Mutex::new(Inner {
+				ttls: Endpoint::ALL.iter().map(|&e| (e, e.default_ttl())).collect(),
+				max_entries: Self::DEFAULT_MAX_ENTRIES,
+				max_bytes: Self::DEFAULT_MAX_BYTES,
+				entries: HashMap::new(),
+				bytes: 0,
+				clock: 0,
+			})),
+		}
+	}
+	
+	fn lock(&self) -> MutexGuard<'_, Inner> {
+		//The cache stays consistent even if a thread panicked while holding the lock
+		self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+	
+	///How long responses from `endpoint` stay fresh. A TTL of zero disables caching for it.
+	pub fn ttl(self, endpoint: Endpoint, ttl: Duration) -> Self {
+		self.lock().ttls.insert(endpoint, ttl);
+		self
+	}
+	
+	///The maximum amount of responses to keep
+	pub fn max_entries(self, max_entries: usize) -> Self {
+		self.lock().max_entries = max_entries;
+		self
+	}
+	
+	///The maximum total size of the response bodies to keep, in bytes
+	pub fn max_bytes(self, max_bytes: usize) -> Self {
+		self.lock().max_bytes = max_bytes;
+		self
+	}
+	
+	///The fresh cached body for a request path, if a transport with this [scope](crate::transport::Transport::scope) received one
+	pub fn get(&self, scope: &str, path: &str) -> Option<Arc<str>> {
+		self.get_at(scope, path, Instant::now())
+	}
+	
+	fn get_at(&self, scope: &str, path: &str, now: Instant) -> Option<Arc<str>> {
+		let mut inner = self.lock();
+		inner.clock += 1;
+		let clock = inner.clock;
+		let key = (scope.to_owned(), cache_key(path));
+		
+		let entry = inner.entries.get_mut(&key)?;
+		if entry.expires > now {
+			entry.last_used = clock;
+			Some(entry.body.clone())
+		} else {
+			inner.remove(&key);
+			None
+		}
+	}
+	
+	///Caches the body of the response to a request path, received by a transport with this [scope](crate::transport::Transport::scope)
+	pub fn insert(&self, scope: &str, path: &str, body: &str) {
+		self.insert_at(scope, path, body, Instant::now());
+	}
+	
+	fn insert_at(&self, scope: &str, path: &str, body: &str, now: Instant) {
+		let mut inner = self.lock();
+		let endpoint = Endpoint::of(path);
+		let ttl = inner.ttls.get(&endpoint).copied().unwrap_or_default();
+		if ttl.is_zero() || body.len() > inner.max_bytes {
+			return;
+		}
+		
+		inner.clock += 1;
+		let key = (scope.to_owned(), cache_key(path));
+		inner.remove(&key);
+		let entry = Entry {
+			endpoint,
+			body: body.into(),
+			expires: now + ttl,
+			last_used: inner.clock,
+		};
+		inner.bytes += body.len();
+		inner.entries.insert(key, entry);
+		inner.evict(now);
+	}
+	
+	///Removes all responses from an endpoint
+	pub fn invalidate(&self, endpoint: Endpoint) {
+		let mut inner = self.lock();
+		inner.entries.retain(|_, entry| entry.endpoint != endpoint);
+		inner.bytes = inner.entries.values().map(|entry| entry.body.len()).sum();
+	}
+	
+	///Removes the responses to a request path, for every scope
+	pub fn invalidate_path(&self, path: &str) {
+		let key = cache_key(path);
+		self.invalidate_matching(|matching| matching == key);
+	}
+	
+	///Removes all responses whose [cache key](cache_key) matches, for every scope
+	pub fn invalidate_matching(&self, mut matches: impl FnMut(&str) -> bool) {
+		let mut inner = self.lock();
+		let keys = inner.entries.keys()
+			.filter(|(_, key)| matches(key))
+			.cloned()
+			.collect::<Vec<_>>();
+		for key in keys {
+			inner.remove(&key);
+		}
+	}
+	
+	///Removes all responses
+	pub fn clear(&self) {
+		let mut inner = self.lock();
+		inner.entries.clear();
+		inner.bytes = 0;
+	}
+	
+	///The amount of cached responses, including ones that expired but haven't been cleaned up yet
+	pub fn len(&self) -> usize {
+		self.lock().entries.len()
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
+
+impl Inner {
+	fn remove(&mut self, key: &(String, String)) {
+		if let Some(entry) = self.entries.remove(key) {
+			self.bytes -= entry.body.len();
+		}
+	}
+	
+	fn over_limit(&self) -> bool {
+		self.entries.len() > self.max_entries || self.bytes > self.max_bytes
+	}
+	
+	///Removes expired entries, then the least recently used ones until the cache is within its bounds
+	fn evict(&mut self, now: Instant) {
+		if !self.over_limit() {
+			return;
+		}
+		
+		let expired = self.entries.iter()
+			.filter(|(_, entry)| entry.expires <= now)
+			.map(|(key, _)| key.clone())
+			.collect::<Vec<_>>();
+		for key in expired {
+			self.remove(&key);
+		}
+		
+		while self.over_limit() {
+			let Some(oldest) = self.entries.iter().min_by_key(|(_, entry)| entry.last_used).map(|(key, _)| key.clone()) else {
+				break;
+			};
+			self.remove(&oldest);
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn keys() {
+		assert_eq!(cache_key("levelhead/levels?tower=true&limit=5"), cache_key("levelhead/levels?limit=5&tower=true"));
+		assert_ne!(cache_key("levelhead/levels?limit=5"), cache_key("levelhead/players?limit=5"));
+		assert_eq!(Endpoint::of("levelhead/levels?limit=5"), Endpoint::Levels);
+		assert_eq!(Endpoint::of("delegation/keys/@this"), Endpoint::KeyInfo);
+	}
+	
+	#[test]
+	fn expires() {
+		let cache = ResponseCache::new()
+			.ttl(Endpoint::Levels, Duration::from_secs(10))
+			.ttl(Endpoint::Players, Duration::ZERO);
+		let start = Instant::now();
+		
+		cache.insert_at("", "levelhead/levels?limit=5", "levels", start);
+		cache.insert_at("", "levelhead/players?limit=5", "players", start);
+		
+		assert_eq!(cache.get_at("", "levelhead/levels?limit=5", start + Duration::from_secs(9)).as_deref(), Some("levels"));
+		assert_eq!(cache.get_at("", "levelhead/levels?limit=5", start + Duration::from_secs(10)), None);
+		assert_eq!(cache.get_at("", "levelhead/players?limit=5", start), None);
+		//Other scopes don't get it
+		assert_eq!(cache.get_at("other key", "levelhead/levels?limit=5", start), None);
+		assert!(cache.is_empty());
+	}
+	
+	#[test]
+	fn bounded() {
+		let cache = ResponseCache::new().max_entries(2).max_bytes(10);
+		let now = Instant::now();
+		
+		cache.insert_at("", "levelhead/levels?a", "1", now);
+		cache.insert_at("", "levelhead/levels?b", "2", now);
+		//Makes b the least recently used
+		cache.get_at("", "levelhead/levels?a", now);
+		cache.insert_at("", "levelhead/levels?c", "3", now);
+		
+		assert!(cache.get_at("", "levelhead/levels?b", now).is_none());
+		assert!(cache.get_at("", "levelhead/levels?a", now).is_some());
+		
+		//Too many bytes, c was used the longest ago
+		cache.insert_at("", "levelhead/levels?d", "123456789", now);
+		assert_eq!(cache.len(), 2);
+		assert!(cache.get_at("", "levelhead/levels?c", now).is_none());
+		
+		cache.insert_at("", "levelhead/levels?e", "way too large to cache", now);
+		assert!(cache.get_at("", "levelhead/levels?e", now).is_none());
+	}
+	
+	#[test]
+	fn invalidates() {
+		let cache = ResponseCache::new();
+		cache.insert("", "levelhead/levels?a", "1");
+		cache.insert("", "levelhead/levels?b", "2");
+		cache.insert("", "levelhead/aliases?c", "3");
+		cache.insert("other key", "levelhead/levels?a", "4");
+		
+		cache.invalidate_path("levelhead/levels?a");
+		assert_eq!(cache.len(), 2);
+		
+		cache.invalidate(Endpoint::Levels);
+		assert_eq!(cache.len(), 1);
+		assert!(cache.get("", "levelhead/aliases?c").is_some());
+		
+		cache.clear();
+		assert!(cache.is_empty());
+	}
+}
@@ -0,0 +1,511 @@
+/*!
+
+A high-level client for Rumpus CE.
+
+[RumpusClient] sends its requests through a [Transport], parses the responses,
+and applies a [FieldPolicy] to them.
+Besides the generic [get](RumpusClient::get), it has methods for the endpoints and for paging through searches:
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use futures_util::StreamExt;
+use rumpus_ce::{client::RumpusClient, query::*};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+
+let level = client.level("abcdefg").await?;
+
+let mut pages = client.pages(LevelSearch::new().newest_first().limit(64)?);
+while let Some(page) = pages.next().await {
+	for level in page? {
+		println!("{}", level.title);
+	}
+}
+# Ok(())
+# }
+```
+
+*/
+
+use std::{fmt, sync::Arc};
+use futures_util::stream::{self, Stream, StreamExt};
+use restson::RestPath;
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+#[cfg(feature="sqlite-cache")]
+use super::sqlite_cache::{Cached, Freshness, SqliteCache, SqliteCacheError};
+use super::{
+	cache::ResponseCache,
+	environment::Environment,
+	query::{AliasSearch, LevelSearch, LevelSortProperty, LimitError, PageError, PlayerSearch},
+	records::PlayerRecords,
+	trace::RequestSpan,
+	transport::{scope_of, RestsonTransport, Transport, TransportError},
+	types::*,
+	unknown::{FieldPolicy, UnknownFields, UnknownFieldsError},
+};
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+	#[error(transparent)]
+	Transport(#[from] TransportError),
+	///Building the client or the path of a request failed
+	#[error(transparent)]
+	Rest(#[from] restson::Error),
+	#[error("couldn't parse response: {source}")]
+	Parse {
+		source: serde_json::Error,
+		body: String,
+	},
+	#[error(transparent)]
+	UnknownFields(#[from] UnknownFieldsError),
+	#[error(transparent)]
+	Page(#[from] PageError),
+	#[error(transparent)]
+	Limit(#[from] LimitError),
+	///Rumpus responded without data, usually with a message explaining why
+	#[error("response contained no data: {}", .message.as_deref().unwrap_or("no message"))]
+	NoData {
+		message: Option<String>,
+	},
+	///The runtime of the [blocking client](crate::blocking) couldn't be started
+	#[cfg(feature="blocking")]
+	#[error("couldn't start the runtime: {0}")]
+	Runtime(std::io::Error),
+	#[cfg(feature="sqlite-cache")]
+	#[error(transparent)]
+	SqliteCache(#[from] SqliteCacheError),
+}
+
+///A client for Rumpus CE, see the [module docs](self)
+#[derive(Clone)]
+pub struct RumpusClient {
+	transport: Arc<dyn Transport>,
+	field_policy: FieldPolicy,
+	cache: Option<ResponseCache>,
+	#[cfg(feature="sqlite-cache")]
+	sqlite_cache: Option<SqliteCache>,
+}
+
+impl RumpusClient {
+	///Sends requests through the given transport
+	pub fn new(transport: impl Transport + 'static) -> Self {
+		Self {
+			transport: Arc::new(transport),
+			field_policy: FieldPolicy::default(),
+			cache: None,
+			#[cfg(feature="sqlite-cache")]
+			sqlite_cache: None,
+		}
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport] with a given delegation key and url/endpoint.
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If `url` is not a valid URL
+	*/
+	pub fn with_key_url(key: impl AsRef<str>, url: impl AsRef<str>) -> Result<Self, ClientError> {
+		Ok(Self::new(RestsonTransport::with_key_url(key, url)?))
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport] with a given delegation key, talking to `environment`.
+	
+	# Errors
+	If `key` is not a valid HTTP header.
+	*/
+	pub fn with_key_environment(key: impl AsRef<str>, environment: &Environment) -> Result<Self, ClientError> {
+		Self::with_key_url(key, environment)
+	}
+	
+	/**
+	Creates a client using a [RestsonTransport] with a given delegation key and using [the default API url/endpoint](super::API_URL).
+	
+	# Errors
+	If `key` is not a valid HTTP header.
+	*/
+	pub fn with_key(key: impl AsRef<str>) -> Result<Self, ClientError> {
+		Ok(Self::new(RestsonTransport::with_key(key)?))
+	}
+	
+	///What to do with fields in responses that this crate doesn't know about
+	pub fn field_policy(mut self, policy: FieldPolicy) -> Self {
+		self.field_policy = policy;
+		self
+	}
+	
+	///Serve responses from this cache while they're fresh, and cache new ones in it
+	pub fn cache(mut self, cache: ResponseCache) -> Self {
+		self.cache = Some(cache);
+		self
+	}
+	
+	///Store every fetched level, player and alias in this cache, and serve lookups from it, see [crate::sqlite_cache]
+	#[cfg(feature="sqlite-cache")]
+	pub fn sqlite_cache(mut self, cache: SqliteCache) -> Self {
+		self.sqlite_cache = Some(cache);
+		self
+	}
+	
+	/**
+	Fetches the endpoint `T` is returned from.
+	
+	# Errors
+	- If the request failed
+	- If the response couldn't be parsed as `T`
+	- If the [field policy](Self::field_policy) rejects the response
+	*/
+	pub async fn get<U, T>(&self, params: U) -> Result<T, ClientError>
+	where
+		T: RestPath<U> + DeserializeOwned + UnknownFields,
+	{
+		self.get_with_retry(params, 0).await
+	}
+	
+	///Like [Self::get], for the `retry`th retry of a request, which gets recorded on its span
+	pub(crate) async fn get_with_retry<U, T>(&self, params: U, retry: u32) -> Result<T, ClientError>
+	where
+		T: RestPath<U> + DeserializeOwned + UnknownFields,
+	{
+		let path = T::get_path(params)?;
+		let span = RequestSpan::new(&path);
+		span.retry(retry);
+		
+		let scope = self.cache.as_ref().map(|_| scope_of(&*self.transport)).unwrap_or_default();
+		let cached = self.cache.as_ref().and_then(|cache| cache.get(&scope, &path));
+		span.cache(self.cache.as_ref().map(|_| cached.is_some()));
+		let body = match &cached {
+			Some(body) => body.to_string(),
+			None => span.send(&*self.transport, &path).await?,
+		};
+		
+		let checked = match serde_json::from_str::<T>(&body) {
+			Ok(response) => self.field_policy.check(&response).map(|_| response).map_err(ClientError::from),
+			Err(source) => Err(ClientError::Parse { source, body: body.clone() }),
+		};
+		let response = checked.inspect_err(|err| span.error(err))?;
+		
+		//Only cache responses that are usable
+		if let (Some(cache), None) = (&self.cache, cached) {
+			cache.insert(&scope, &path, &body);
+		}
+		Ok(response)
+	}
+	
+	///Fetches an endpoint and unwraps the data from the response
+	async fn data<U, D>(&self, params: U) -> Result<D, ClientError>
+	where
+		Rumpus<D>: RestPath<U> + DeserializeOwned + UnknownFields,
+	{
+		let response = self.get::<U, Rumpus<D>>(params).await?;
+		response.data.ok_or(ClientError::NoData { message: response.message })
+	}
+	
+	///Information about the delegation key this client uses
+	pub async fn key_info(&self) -> Result<DelegationKeyInfo, ClientError> {
+		self.data(()).await
+	}
+	
+	///A single page of players
+	pub async fn players(&self, search: PlayerSearch) -> Result<Vec<Player>, ClientError> {
+		let players = self.data(search).await?;
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let now = std::time::SystemTime::now();
+			return Ok(off_runtime(cache, move |cache| cache.store_players(&players, now).map(|()| players)).await?);
+		}
+		Ok(players)
+	}
+	
+	///A single page of levels
+	pub async fn levels(&self, search: LevelSearch) -> Result<Vec<Level>, ClientError> {
+		let levels = self.data(search).await?;
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let now = std::time::SystemTime::now();
+			return Ok(off_runtime(cache, move |cache| cache.store_levels(&levels, now).map(|()| levels)).await?);
+		}
+		Ok(levels)
+	}
+	
+	pub async fn aliases(&self, search: AliasSearch) -> Result<Vec<Alias>, ClientError> {
+		let aliases = self.data(search).await?;
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let now = std::time::SystemTime::now();
+			return Ok(off_runtime(cache, move |cache| cache.store_aliases(&aliases, now).map(|()| aliases)).await?);
+		}
+		Ok(aliases)
+	}
+	
+	///Looks up a single player, including their alias
+	pub async fn player(&self, user_id: impl Into<String>) -> Result<Option<Player>, ClientError> {
+		let user_id = user_id.into();
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let id = user_id.clone();
+			let cached = off_runtime(cache, move |cache| cache.player(&id)).await?.filter(|p| p.value.alias.is_some());
+			let id = user_id.clone();
+			if let Some(player) = self.serve_cached(cached, |client| async move { client.fetch_player(id).await }) {
+				return Ok(Some(player));
+			}
+		}
+		self.fetch_player(user_id).await
+	}
+	
+	async fn fetch_player(&self, user_id: String) -> Result<Option<Player>, ClientError> {
+		let search = PlayerSearch::new()
+			.user_ids(vec![user_id])?
+			.include_aliases(true);
+		Ok(self.players(search).await?.into_iter().next())
+	}
+	
+	///Looks up a single level, including its stats
+	pub async fn level(&self, level_id: impl Into<String>) -> Result<Option<Level>, ClientError> {
+		let level_id = level_id.into();
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let id = level_id.clone();
+			let cached = off_runtime(cache, move |cache| cache.level(&id)).await?.filter(|l| l.value.stats.is_some());
+			let id = level_id.clone();
+			if let Some(level) = self.serve_cached(cached, |client| async move { client.fetch_level(id).await }) {
+				return Ok(Some(level));
+			}
+		}
+		self.fetch_level(level_id).await
+	}
+	
+	async fn fetch_level(&self, level_id: String) -> Result<Option<Level>, ClientError> {
+		let search = LevelSearch::new()
+			.level_ids(vec![level_id])?
+			.include_stats(true);
+		Ok(self.levels(search).await?.into_iter().next())
+	}
+	
+	pub async fn alias(&self, user_id: impl Into<String>) -> Result<Option<Alias>, ClientError> {
+		let user_id = user_id.into();
+		#[cfg(feature="sqlite-cache")]
+		if let Some(cache) = &self.sqlite_cache {
+			let id = user_id.clone();
+			let cached = off_runtime(cache, move |cache| cache.alias(&id)).await?;
+			let id = user_id.clone();
+			if let Some(alias) = self.serve_cached(cached, |client| async move { client.fetch_alias(id).await }) {
+				return Ok(Some(alias));
+			}
+		}
+		self.fetch_alias(user_id).await
+	}
+	
+	async fn fetch_alias(&self, user_id: String) -> Result<Option<Alias>, ClientError> {
+		let search = AliasSearch::new().user_ids(vec![user_id])?;
+		Ok(self.aliases(search).await?.into_iter().next())
+	}
+	
+	/**
+	The leaderboard records the player with `user_id` holds on the levels `search` finds.
+	
+	Rumpus can't look up the records of a player, so this pages through every level of the search with its records included,
+	see [crate::records]. Narrow the search down (e.g. to tower trials) to keep the amount of requests down.
+	
+	# Errors
+	If a request failed
+	*/
+	pub async fn player_records(&self, user_id: impl Into<String>, search: LevelSearch) -> Result<PlayerRecords, ClientError> {
+		let mut held = PlayerRecords::new(user_id);
+		let mut pages = self.pages(search.include_records(true));
+		while let Some(page) = pages.next().await {
+			held.extend(&page?);
+		}
+		Ok(held)
+	}
+	
+	/**
+	Returns a cached entity if it's still usable.
+	
+	Stale entities get refreshed in the background, if there is a tokio runtime to do so.
+	Errors while refreshing are ignored: the next lookup will try again.
+	*/
+	#[cfg(feature="sqlite-cache")]
+	fn serve_cached<T, F, Fut>(&self, cached: Option<Cached<T>>, refresh: F) -> Option<T>
+	where
+		F: FnOnce(Self) -> Fut,
+		Fut: std::future::Future<Output = Result<Option<T>, ClientError>> + Send + 'static,
+	{
+		let cache = self.sqlite_cache.as_ref()?;
+		let cached = cached?;
+		match cache.freshness(cached.fetched_at) {
+			Freshness::Fresh => Some(cached.value),
+			Freshness::Stale => {
+				if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+					let refresh = refresh(self.clone());
+					runtime.spawn(async move {
+						let _ = refresh.await;
+					});
+				}
+				Some(cached.value)
+			},
+			Freshness::Expired => None,
+		}
+	}
+	
+	/**
+	Pages through all results of a search, one page per item of the stream.
+	
+	The stream ends after the first empty page, or the first page smaller than the limit of the search.
+	It also ends after the first error.
+	See [PagedSearch::prepare] for how the search gets adjusted to allow paging.
+	*/
+	pub fn pages<S>(&self, search: S) -> impl Stream<Item = Result<Vec<S::Item>, ClientError>> + Send + Unpin + '_
+	where
+		S: PagedSearch + 'static,
+		Rumpus<Vec<S::Item>>: RestPath<S> + DeserializeOwned + UnknownFields,
+	{
+		Box::pin(stream::unfold(Some(search.prepare()), move |search| async move {
+			let search = search?;
+			let page = match self.data::<S, Vec<S::Item>>(search.clone()).await {
+				Ok(page) => page,
+				Err(err) => return Some((Err(err), None)),
+			};
+			#[cfg(feature="sqlite-cache")]
+			let page = match &self.sqlite_cache {
+				None => page,
+				Some(cache) => {
+					let now = std::time::SystemTime::now();
+					match off_runtime(cache, move |cache| S::store(cache, &page, now).map(|()| page)).await {
+						Ok(page) => page,
+						Err(err) => return Some((Err(err.into()), None)),
+					}
+				},
+			};
+			let next = match page.last() {
+				None => return None,
+				Some(_) if search.page_limit().is_some_and(|limit| page.len() < limit as usize) => None,
+				Some(last) => match search.next_page(last) {
+					Ok(next) => Some(next),
+					Err(err) => return Some((Err(err.into()), None)),
+				},
+			};
+			Some((Ok(page), next))
+		}))
+	}
+}
+
+/**
+Runs a blocking call on the SQLite cache on tokio's blocking threads, so it doesn't hold up other tasks.
+
+Without a tokio runtime, the call just runs on the current thread.
+*/
+#[cfg(feature="sqlite-cache")]
+async fn off_runtime<T, F>(cache: &SqliteCache, call: F) -> T
+where
+	T: Send + 'static,
+	F: FnOnce(&SqliteCache) -> T + Send + 'static,
+{
+	let cache = cache.clone();
+	match tokio::runtime::Handle::try_current() {
+		Ok(runtime) => match runtime.spawn_blocking(move || call(&cache)).await {
+			Ok(result) => result,
+			Err(err) => std::panic::resume_unwind(err.into_panic()),
+		},
+		Err(_) => call(&cache),
+	}
+}
+
+impl fmt::Debug for RumpusClient {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut debug = f.debug_struct("RumpusClient");
+		debug
+			.field("field_policy", &self.field_policy)
+			.field("cache", &self.cache);
+		#[cfg(feature="sqlite-cache")]
+		debug.field("sqlite_cache", &self.sqlite_cache);
+		debug.finish_non_exhaustive()
+	}
+}
+
+///Searches [RumpusClient::pages] can page through
+pub trait PagedSearch: Clone + Send + Sync {
+	type Item: Send;
+	
+	///Adjusts the search so it can be paged through, by making sure it's sorted and contains the data paging needs
+	fn prepare(self) -> Self;
+	
+	///The maximum amount of results per page, if set
+	fn page_limit(&self) -> Option<u8>;
+	
+	/**
+	The search for the page after the one ending with `last`.
+	
+	# Errors
+	If the search can't be paged, see [PageError].
+	*/
+	fn next_page(self, last: &Self::Item) -> Result<Self, PageError>;
+	
+	///Stores a page in the cache of the client paging through it, like [RumpusClient::levels] and co. do
+	#[cfg(feature="sqlite-cache")]
+	fn store(cache: &SqliteCache, page: &[Self::Item], fetched_at: std::time::SystemTime) -> Result<(), SqliteCacheError> {
+		let _ = (cache, page, fetched_at);
+		Ok(())
+	}
+}
+
+impl PagedSearch for PlayerSearch {
+	type Item = Player;
+	
+	///Sorts on newest first if the search isn't sorted yet
+	fn prepare(self) -> Self {
+		if self.sort.is_some() {
+			self
+		} else {
+			self.newest_first()
+		}
+	}
+	
+	fn page_limit(&self) -> Option<u8> {
+		self.limit
+	}
+	
+	fn next_page(self, last: &Player) -> Result<Self, PageError> {
+		self.page_after(last)
+	}
+	
+	#[cfg(feature="sqlite-cache")]
+	fn store(cache: &SqliteCache, page: &[Player], fetched_at: std::time::SystemTime) -> Result<(), SqliteCacheError> {
+		cache.store_players(page, fetched_at)
+	}
+}
+
+impl PagedSearch for LevelSearch {
+	type Item = Level;
+	
+	///Sorts on newest first if the search isn't sorted yet,
+	///and includes stats when sorting on one of them unless they were explicitly excluded
+	fn prepare(self) -> Self {
+		let this = if self.sort.is_some() {
+			self
+		} else {
+			self.newest_first()
+		};
+		let on_stat = this.sort.as_ref().is_some_and(|sort| *sort.property() != LevelSortProperty::CreatedAt);
+		if on_stat && this.include_stats.is_none() {
+			this.include_stats(true)
+		} else {
+			this
+		}
+	}
+	
+	fn page_limit(&self) -> Option<u8> {
+		self.limit
+	}
+	
+	fn next_page(self, last: &Level) -> Result<Self, PageError> {
+		self.page_after(last)
+	}
+	
+	#[cfg(feature="sqlite-cache")]
+	fn store(cache: &SqliteCache, page: &[Level], fetched_at: std::time::SystemTime) -> Result<(), SqliteCacheError> {
+		cache.store_levels(page, fetched_at)
+	}
+}
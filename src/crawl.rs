@@ -0,0 +1,415 @@
+/*!
+
+Resumable crawlers that archive everything a search returns, e.g. every published level or every player.
+
+A [Crawler] walks through a search ordered on creation date, oldest first,
+and appends everything it finds to an archive of newline delimited JSON.
+Every line is an [Archived] snapshot: the entity as it was when it was fetched, including its stats at that time.
+After every page, it saves a [Checkpoint] of how far it got,
+so a crawl that gets interrupted continues where it left off the next time it's run.
+Running a finished crawl again picks up whatever was created since.
+
+Pages are appended before the checkpoint is saved,
+so if the crawler gets interrupted in between, the next run will archive that page again.
+Readers of the archive should deduplicate on the id of the entities.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::crawl::CrawlError> {
+use rumpus_ce::{client::RumpusClient, crawl::Crawler};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+let progress = Crawler::levels(client, "archive").run().await?;
+println!("archived {} new levels", progress.archived);
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{self, BufRead, BufReader, BufWriter, Write},
+	marker::PhantomData,
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use restson::RestPath;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use super::{
+	client::{ClientError, PagedSearch, RumpusClient},
+	query::{LevelSearch, PageError, PlayerSearch},
+	types::{Level, Player, Rumpus},
+	unknown::UnknownFields,
+};
+
+#[derive(Error, Debug)]
+pub enum CrawlError {
+	#[error(transparent)]
+	Client(#[from] ClientError),
+	#[error("couldn't access {path}: {source}")]
+	Io {
+		path: PathBuf,
+		source: io::Error,
+	},
+	#[error("couldn't parse {path}: {source}")]
+	Parse {
+		path: PathBuf,
+		source: serde_json::Error,
+	},
+}
+
+impl From<PageError> for CrawlError {
+	fn from(err: PageError) -> Self {
+		Self::Client(err.into())
+	}
+}
+
+///How far a crawl got
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all="camelCase")]
+pub struct Checkpoint {
+	///Creation date of the last archived entity
+	pub created_at: Option<String>,
+	///Id of the last archived entity, to skip the entities created at the same time that were already archived
+	pub tiebreaker_item_id: Option<String>,
+	///How many entities have been archived in total
+	pub archived: u64,
+}
+
+impl Checkpoint {
+	/**
+	Reads a checkpoint, or starts from scratch if there is none yet.
+	
+	# Errors
+	If the file exists but couldn't be read or parsed.
+	*/
+	pub fn load(path: impl AsRef<Path>) -> Result<Self, CrawlError> {
+		let path = path.as_ref();
+		match fs::read_to_string(path) {
+			Ok(json) => serde_json::from_str(&json).map_err(|source| CrawlError::Parse { path: path.to_owned(), source }),
+			Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+			Err(source) => Err(CrawlError::Io { path: path.to_owned(), source }),
+		}
+	}
+	
+	/**
+	Writes the checkpoint, replacing the previous one in a single step so it never ends up half written.
+	
+	# Errors
+	If the file couldn't be written.
+	*/
+	pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CrawlError> {
+		let path = path.as_ref();
+		let temporary = path.with_extension("tmp");
+		let json = serde_json::to_string_pretty(self).expect("checkpoints serialize");
+		fs::write(&temporary, json + "\n")
+			.and_then(|_| fs::rename(&temporary, path))
+			.map_err(|source| CrawlError::Io { path: path.to_owned(), source })
+	}
+}
+
+///Searches a [Crawler] can crawl through
+pub trait CrawlSearch: PagedSearch + 'static {
+	///Used to name the archive and checkpoint files
+	const NAME: &'static str;
+	
+	///Sorts the search on creation date, oldest first, continuing after the checkpoint
+	fn resume(self, checkpoint: &Checkpoint) -> Self;
+	
+	///The creation date and id of an entity, to checkpoint after it
+	fn cursor(item: &Self::Item) -> (&str, &str);
+}
+
+//Both searches have the same paging parameters, and their entities the same cursor fields
+macro_rules! crawl_search {
+	($search:ty, $item:ty, $name:literal) => {
+		impl CrawlSearch for $search {
+			const NAME: &'static str = $name;
+			
+			fn resume(self, checkpoint: &Checkpoint) -> Self {
+				let mut this = self.oldest_first();
+				if this.limit.is_none() {
+					this.limit = Some(Self::MAX_LIMIT as u8);
+				}
+				if checkpoint.created_at.is_some() {
+					this.min_created_at = checkpoint.created_at.clone();
+					this.tiebreaker_item_id = checkpoint.tiebreaker_item_id.clone();
+				}
+				this
+			}
+			
+			fn cursor(item: &$item) -> (&str, &str) {
+				(&item.created_at, &item.id)
+			}
+		}
+	};
+}
+
+crawl_search!(LevelSearch, Level, "levels");
+crawl_search!(PlayerSearch, Player, "players");
+
+///How much a single [Crawler::run] archived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CrawlProgress {
+	///Entities archived during this run
+	pub archived: u64,
+	///Entities archived in total, including earlier runs
+	pub total: u64,
+	///Pages fetched during this run
+	pub pages: u64,
+}
+
+///A resumable crawl, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct Crawler<S> {
+	client: RumpusClient,
+	search: S,
+	directory: PathBuf,
+	interval: Duration,
+	max_retries: u32,
+	retry_delay: Duration,
+	max_pages: Option<u64>,
+}
+
+impl Crawler<LevelSearch> {
+	/**
+	Crawls every level, with their stats and records, including those only playable on beta clients.
+	
+	Customize what gets crawled with [Self::search].
+	*/
+	pub fn levels(client: RumpusClient, directory: impl Into<PathBuf>) -> Self {
+		let search = LevelSearch::new()
+			.include_stats(true)
+			.include_records(true)
+			.include_beta(true);
+		Self::new(client, search, directory)
+	}
+}
+
+impl Crawler<PlayerSearch> {
+	/**
+	Crawls every player, with their aliases.
+	
+	The aliases make it possible to tell which accounts [were deleted](Player::appears_deleted).
+	Customize what gets crawled with [Self::search].
+	*/
+	pub fn players(client: RumpusClient, directory: impl Into<PathBuf>) -> Self {
+		let search = PlayerSearch::new().include_aliases(true);
+		Self::new(client, search, directory)
+	}
+}
+
+impl<S> Crawler<S>
+where
+	S: CrawlSearch,
+	Rumpus<Vec<S::Item>>: RestPath<S> + DeserializeOwned + UnknownFields,
+	S::Item: Serialize,
+{
+	pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+	pub const DEFAULT_MAX_RETRIES: u32 = 5;
+	pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+	
+	///Crawls everything `search` finds into `directory`. The sort and paging parameters of the search are overwritten.
+	pub fn new(client: RumpusClient, search: S, directory: impl Into<PathBuf>) -> Self {
+		Self {
+			client,
+			search,
+			directory: directory.into(),
+			interval: Self::DEFAULT_INTERVAL,
+			max_retries: Self::DEFAULT_MAX_RETRIES,
+			retry_delay: Self::DEFAULT_RETRY_DELAY,
+			max_pages: None,
+		}
+	}
+	
+	///What to crawl. The sort and paging parameters of the search are overwritten.
+	pub fn search(mut self, search: S) -> Self {
+		self.search = search;
+		self
+	}
+	
+	///How long to wait between requests, to stay within the rate limit
+	pub fn interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+	
+	///How often to retry after hitting the rate limit (HTTP 429) or a server error, waiting twice as long every time
+	pub fn retries(mut self, max_retries: u32, first_delay: Duration) -> Self {
+		self.max_retries = max_retries;
+		self.retry_delay = first_delay;
+		self
+	}
+	
+	///Stop after this many pages, to spread a crawl over multiple runs
+	pub fn max_pages(mut self, max_pages: u64) -> Self {
+		self.max_pages = Some(max_pages);
+		self
+	}
+	
+	///The file the entities get appended to
+	pub fn archive_path(&self) -> PathBuf {
+		self.directory.join(format!("{}.ndjson", S::NAME))
+	}
+	
+	///The file the progress gets saved to
+	pub fn checkpoint_path(&self) -> PathBuf {
+		self.directory.join(format!("{}.checkpoint.json", S::NAME))
+	}
+	
+	/**
+	Crawls until there is nothing new left, or [the maximum amount of pages](Self::max_pages) was reached.
+	
+	# Errors
+	- If a request failed, after retrying if that makes sense
+	- If the archive or checkpoint couldn't be read or written
+	*/
+	pub async fn run(&self) -> Result<CrawlProgress, CrawlError> {
+		fs::create_dir_all(&self.directory)
+			.map_err(|source| CrawlError::Io { path: self.directory.clone(), source })?;
+		
+		let checkpoint_path = self.checkpoint_path();
+		let mut checkpoint = Checkpoint::load(&checkpoint_path)?;
+		let mut archive = Archive::<S::Item>::append(self.archive_path())?;
+		let mut progress = CrawlProgress {
+			total: checkpoint.archived,
+			..Default::default()
+		};
+		
+		let mut search = self.search.clone().resume(&checkpoint);
+		while self.max_pages.is_none_or(|max| progress.pages < max) {
+			if progress.pages > 0 {
+				tokio::time::sleep(self.interval).await;
+			}
+			let page = self.fetch(search.clone()).await?;
+			let fetched_at = SystemTime::now();
+			progress.pages += 1;
+			
+			let Some(last) = page.last() else {
+				break;
+			};
+			archive.write(&page, fetched_at)?;
+			
+			let (created_at, id) = S::cursor(last);
+			checkpoint.created_at = Some(created_at.to_owned());
+			checkpoint.tiebreaker_item_id = Some(id.to_owned());
+			checkpoint.archived += page.len() as u64;
+			checkpoint.save(&checkpoint_path)?;
+			
+			progress.archived += page.len() as u64;
+			progress.total = checkpoint.archived;
+			search = search.next_page(last)?;
+		}
+		
+		Ok(progress)
+	}
+	
+	///Fetches a page, retrying when rate limited or when the server had a problem
+	async fn fetch(&self, search: S) -> Result<Vec<S::Item>, CrawlError> {
+		let mut delay = self.retry_delay;
+		let mut retries = 0;
+		loop {
+			match self.client.get_with_retry::<S, Rumpus<Vec<S::Item>>>(search.clone(), retries).await {
+				Ok(response) => return Ok(response.data.unwrap_or_default()),
+				Err(ClientError::Transport(err)) if retries < self.max_retries && err.status().is_some_and(retryable) => {
+					#[cfg(feature="tracing")]
+					tracing::warn!(status = err.status(), retry = retries + 1, delay_ms = delay.as_millis() as u64, "retrying page");
+					#[cfg(feature="metrics")]
+					if err.status() == Some(429) {
+						crate::metrics::rate_limit_wait("crawl", delay);
+					}
+					tokio::time::sleep(delay).await;
+					delay *= 2;
+					retries += 1;
+				},
+				Err(err) => return Err(err.into()),
+			}
+		}
+	}
+}
+
+fn retryable(status: u16) -> bool {
+	status == 429 || (500..600).contains(&status)
+}
+
+///An entity in an archive
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all="camelCase")]
+pub struct Archived<T> {
+	///Milliseconds since the Unix epoch
+	pub fetched_at: u64,
+	pub item: T,
+}
+
+impl<T> Archived<T> {
+	pub fn fetched_at(&self) -> SystemTime {
+		UNIX_EPOCH + Duration::from_millis(self.fetched_at)
+	}
+}
+
+///An append-only file of [Archived] entities as newline delimited JSON
+#[derive(Debug)]
+pub struct Archive<T> {
+	path: PathBuf,
+	writer: BufWriter<File>,
+	items: PhantomData<T>,
+}
+
+impl<T: Serialize> Archive<T> {
+	/**
+	Opens an archive to append to, creating it if it doesn't exist yet.
+	
+	# Errors
+	If the file couldn't be opened.
+	*/
+	pub fn append(path: impl Into<PathBuf>) -> Result<Self, CrawlError> {
+		let path = path.into();
+		let file = OpenOptions::new()
+			.create(true)
+			.append(true)
+			.open(&path)
+			.map_err(|source| CrawlError::Io { path: path.clone(), source })?;
+		Ok(Self {
+			path,
+			writer: BufWriter::new(file),
+			items: PhantomData,
+		})
+	}
+	
+	/**
+	Appends the items as fetched at `fetched_at`, and makes sure they're on disk.
+	
+	# Errors
+	If the file couldn't be written.
+	*/
+	pub fn write(&mut self, items: &[T], fetched_at: SystemTime) -> Result<(), CrawlError> {
+		let fetched_at = fetched_at.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64;
+		let result = items.iter()
+			.try_for_each(|item| {
+				serde_json::to_writer(&mut self.writer, &Archived { fetched_at, item })?;
+				self.writer.write_all(b"\n")
+			})
+			.and_then(|_| self.writer.flush())
+			.and_then(|_| self.writer.get_ref().sync_data());
+		result.map_err(|source| CrawlError::Io { path: self.path.clone(), source })
+	}
+}
+
+/**
+Reads the entities of an archive, in the order they were appended.
+
+# Errors
+If the file couldn't be opened. Errors reading or parsing lines are returned by the iterator.
+*/
+pub fn read_archive<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<impl Iterator<Item = Result<Archived<T>, CrawlError>>, CrawlError> {
+	let path = path.as_ref().to_owned();
+	let file = File::open(&path).map_err(|source| CrawlError::Io { path: path.clone(), source })?;
+	let lines = BufReader::new(file).lines()
+		.filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+		.map(move |line| {
+			let line = line.map_err(|source| CrawlError::Io { path: path.clone(), source })?;
+			serde_json::from_str(&line).map_err(|source| CrawlError::Parse { path: path.clone(), source })
+		});
+	Ok(lines)
+}
@@ -18,4 +18,10 @@ impl RestPath<query::LevelSearch> for Rumpus<Vec<Level>> {
 	fn get_path(query: query::LevelSearch) -> Result<String, Error> {
 		Ok(format!("levelhead/levels?{}", query))
 	}
+}
+
+impl RestPath<query::AliasSearch> for Rumpus<Vec<Alias>> {
+	fn get_path(query: query::AliasSearch) -> Result<String, Error> {
+		Ok(format!("levelhead/aliases?{}", query))
+	}
 }
\ No newline at end of file
@@ -0,0 +1,265 @@
+/*!
+
+Switching between the production and the beta API.
+
+An [Environment] says which Rumpus CE to talk to.
+It can be used everywhere a URL can, e.g. `RumpusClient::with_key_url(key, Environment::Beta)`,
+or with [RumpusClient::with_key_environment].
+
+[Environments] runs the same search against two environments and [diffs](Diff) the results,
+to see how the levels of a beta version of the game differ before it gets released:
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use rumpus_ce::{environment::Environments, query::LevelSearch};
+
+let environments = Environments::with_key("your-delegation-key")?;
+let search = LevelSearch::new().include_beta(true).limit(64)?;
+let diff = environments.levels(search).await?;
+for level in &diff.only_second {
+	println!("only on beta: {} ({:?})", level.title, level.game_version);
+}
+for changed in &diff.changed {
+	println!("{} differs in {}", changed.first.level_id, changed.fields.join(", "));
+}
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	collections::HashMap,
+	fmt,
+	str::FromStr,
+};
+use futures_util::future;
+use serde::Serialize;
+use url::Url;
+use super::{
+	client::{ClientError, RumpusClient},
+	query::{LevelSearch, PlayerSearch},
+	types::{Level, Player},
+};
+
+///Which Rumpus CE to talk to
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Environment {
+	///The live API, at [API_URL](crate::API_URL)
+	#[default]
+	Production,
+	///The API of the beta version of the game, at [BETA_URL](crate::BETA_URL)
+	Beta,
+	///Any other API, like a [mock server](crate::mock)
+	Custom(Url),
+}
+
+impl Environment {
+	///The URL of the API
+	pub fn url(&self) -> &str {
+		match self {
+			Self::Production => super::API_URL,
+			Self::Beta => super::BETA_URL,
+			Self::Custom(url) => url.as_str(),
+		}
+	}
+	
+	///[Production](Self::Production) or [Beta](Self::Beta) if `url` is theirs, [Custom](Self::Custom) otherwise
+	pub fn from_url(url: Url) -> Self {
+		match url.as_str() {
+			super::API_URL => Self::Production,
+			super::BETA_URL => Self::Beta,
+			_ => Self::Custom(url),
+		}
+	}
+}
+
+impl AsRef<str> for Environment {
+	fn as_ref(&self) -> &str {
+		self.url()
+	}
+}
+
+///Parses `production`, `beta`, or the URL of an API
+impl FromStr for Environment {
+	type Err = url::ParseError;
+	
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"production" => Ok(Self::Production),
+			"beta" => Ok(Self::Beta),
+			url => Ok(Self::from_url(url.parse()?)),
+		}
+	}
+}
+
+impl fmt::Display for Environment {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Production => write!(f, "production"),
+			Self::Beta => write!(f, "beta"),
+			Self::Custom(url) => write!(f, "{url}"),
+		}
+	}
+}
+
+///Things that can be matched up between environments
+pub trait Comparable: Serialize + PartialEq {
+	///What identifies the same thing in both environments
+	fn identity(&self) -> &str;
+}
+
+impl Comparable for Level {
+	fn identity(&self) -> &str {
+		&self.level_id
+	}
+}
+
+impl Comparable for Player {
+	fn identity(&self) -> &str {
+		&self.user_id
+	}
+}
+
+///Something both environments have, but differently
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changed<T> {
+	pub first: T,
+	pub second: T,
+	///The names of the top-level fields that differ, as Rumpus calls them
+	pub fields: Vec<String>,
+}
+
+///How the results of the same search differ between two environments
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff<T> {
+	///Only returned by the first environment, in its order
+	pub only_first: Vec<T>,
+	///Only returned by the second environment, in its order
+	pub only_second: Vec<T>,
+	///Returned by both, in the order of the first environment
+	pub changed: Vec<Changed<T>>,
+	///How many were returned by both and are the same
+	pub unchanged: usize,
+}
+
+impl<T> Default for Diff<T> {
+	fn default() -> Self {
+		Self {
+			only_first: Vec::new(),
+			only_second: Vec::new(),
+			changed: Vec::new(),
+			unchanged: 0,
+		}
+	}
+}
+
+impl<T: Comparable> Diff<T> {
+	///Matches up the results of two environments by their [identity](Comparable::identity)
+	pub fn new(first: Vec<T>, second: Vec<T>) -> Self {
+		let mut second = second.into_iter().map(Some).collect::<Vec<_>>();
+		let index = second.iter()
+			.enumerate()
+			.filter_map(|(i, item)| Some((item.as_ref()?.identity().to_owned(), i)))
+			.collect::<HashMap<_, _>>();
+		
+		let mut diff = Self::default();
+		for item in first {
+			let Some(other) = index.get(item.identity()).and_then(|&i| second[i].take()) else {
+				diff.only_first.push(item);
+				continue;
+			};
+			if item == other {
+				diff.unchanged += 1;
+			} else {
+				let fields = changed_fields(&item, &other);
+				diff.changed.push(Changed { first: item, second: other, fields });
+			}
+		}
+		diff.only_second = second.into_iter().flatten().collect();
+		diff
+	}
+}
+
+impl<T> Diff<T> {
+	///Whether both environments returned the same
+	pub fn is_empty(&self) -> bool {
+		self.only_first.is_empty() && self.only_second.is_empty() && self.changed.is_empty()
+	}
+}
+
+fn changed_fields(first: &impl Serialize, second: &impl Serialize) -> Vec<String> {
+	let to_object = |value: serde_json::Value| match value {
+		serde_json::Value::Object(object) => object,
+		_ => serde_json::Map::new(),
+	};
+	let first = to_object(serde_json::to_value(first).unwrap_or_default());
+	let mut second = to_object(serde_json::to_value(second).unwrap_or_default());
+	
+	let mut fields = first.into_iter()
+		.filter(|(field, value)| second.remove(field).as_ref() != Some(value))
+		.map(|(field, _)| field)
+		.collect::<Vec<_>>();
+	fields.extend(second.into_iter().map(|(field, _)| field));
+	fields.sort_unstable();
+	fields
+}
+
+///Two clients, to run the same search in both environments, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct Environments {
+	pub first: RumpusClient,
+	pub second: RumpusClient,
+}
+
+impl Environments {
+	pub fn new(first: RumpusClient, second: RumpusClient) -> Self {
+		Self { first, second }
+	}
+	
+	/**
+	Compares [production](Environment::Production) with [beta](Environment::Beta), using the same delegation key for both.
+	
+	# Errors
+	If `key` is not a valid HTTP header.
+	*/
+	pub fn with_key(key: impl AsRef<str>) -> Result<Self, ClientError> {
+		Ok(Self::new(
+			RumpusClient::with_key_environment(&key, &Environment::Production)?,
+			RumpusClient::with_key_environment(&key, &Environment::Beta)?,
+		))
+	}
+	
+	///Runs a level search in both environments at the same time
+	pub async fn levels(&self, search: LevelSearch) -> Result<Diff<Level>, ClientError> {
+		let (first, second) = future::try_join(self.first.levels(search.clone()), self.second.levels(search)).await?;
+		Ok(Diff::new(first, second))
+	}
+	
+	///Runs a player search in both environments at the same time
+	pub async fn players(&self, search: PlayerSearch) -> Result<Diff<Player>, ClientError> {
+		let (first, second) = future::try_join(self.first.players(search.clone()), self.second.players(search)).await?;
+		Ok(Diff::new(first, second))
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn parses() {
+		assert_eq!("beta".parse::<Environment>().unwrap(), Environment::Beta);
+		assert_eq!(crate::API_URL.parse::<Environment>().unwrap(), Environment::Production);
+		let custom = "http://127.0.0.1:8080/api/".parse::<Environment>().unwrap();
+		assert_eq!(custom.url(), "http://127.0.0.1:8080/api/");
+		assert_eq!(custom.to_string(), custom.url());
+		assert!("not a url".parse::<Environment>().is_err());
+		
+		for environment in [Environment::Production, Environment::Beta, custom] {
+			assert_eq!(environment.to_string().parse::<Environment>().unwrap(), environment);
+		}
+	}
+}
@@ -0,0 +1,411 @@
+/*!
+
+Exporting levels and players as flat rows, for spreadsheets and data tools.
+
+Every [Level] and [Player] is flattened into a [LevelRow] or [PlayerRow]:
+nested structs like [LevelStats](crate::types::LevelStats) become columns named after the path of their serde field names,
+e.g. `stats.Likes` or `content.Hazards`, and lists like tags are joined with commas.
+Leaderboards and fields this crate doesn't know about are left out, so the columns don't change between exports.
+Fields that are missing, like the stats of a level fetched without them, become empty cells.
+
+An [Exporter] writes rows as CSV or newline delimited JSON, one entity at a time,
+and can directly consume the pages of a [search](crate::client::RumpusClient::pages):
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::export::ExportError> {
+use std::fs::File;
+use rumpus_ce::{client::RumpusClient, export::Exporter, query::LevelSearch};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+let search = LevelSearch::new().include_stats(true).limit(64)?;
+
+let mut exporter = Exporter::csv(File::create("levels.csv")?);
+exporter.write_pages(client.pages(search)).await?;
+exporter.finish()?;
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	io::{self, Write},
+	marker::PhantomData,
+	pin::pin,
+};
+use futures_util::stream::{Stream, StreamExt};
+use serde::Serialize;
+use thiserror::Error;
+use super::{
+	client::ClientError,
+	query::LimitError,
+	types::{Alias, Level, Player, Stat},
+};
+
+#[derive(Error, Debug)]
+pub enum ExportError {
+	#[error(transparent)]
+	Client(#[from] ClientError),
+	#[error(transparent)]
+	Limit(#[from] LimitError),
+	#[error(transparent)]
+	Io(#[from] io::Error),
+	#[error("couldn't write CSV: {0}")]
+	Csv(#[from] csv::Error),
+	#[error("couldn't write JSON: {0}")]
+	Json(#[from] serde_json::Error),
+}
+
+///Something that can be exported as a flat row
+pub trait Export {
+	type Row<'a>: Serialize where Self: 'a;
+	
+	fn row(&self) -> Self::Row<'_>;
+}
+
+fn alias_name(alias: &Option<Alias>) -> Option<&str> {
+	alias.as_ref().and_then(|a| a.alias.as_deref())
+}
+
+///A [Level] flattened into a single row
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LevelRow<'a> {
+	#[serde(rename="_id")]
+	pub id: &'a str,
+	#[serde(rename="levelId")]
+	pub level_id: &'a str,
+	#[serde(rename="title")]
+	pub title: &'a str,
+	#[serde(rename="userId")]
+	pub user_id: &'a str,
+	#[serde(rename="alias.alias")]
+	pub alias: Option<&'a str>,
+	#[serde(rename="cv")]
+	pub cv: Stat,
+	#[serde(rename="avatarId")]
+	pub avatar_id: &'a str,
+	#[serde(rename="localeId")]
+	pub locale_id: Stat,
+	#[serde(rename="locale")]
+	pub locale: &'a str,
+	#[serde(rename="createdAt")]
+	pub created_at: &'a str,
+	#[serde(rename="updatedAt")]
+	pub updated_at: &'a str,
+	#[serde(rename="tower")]
+	pub tower: Option<bool>,
+	#[serde(rename="dailyBuild")]
+	pub daily_build: Option<bool>,
+	#[serde(rename="towerTrial")]
+	pub tower_trial: bool,
+	#[serde(rename="requiredPlayers")]
+	pub required_players: u8,
+	#[serde(rename="creatorTime")]
+	pub creator_time: f32,
+	#[serde(rename="gameVersion")]
+	pub game_version: Option<&'a str>,
+	#[serde(rename="tags")]
+	pub tags: String,
+	#[serde(rename="tagNames")]
+	pub tag_names: String,
+	#[serde(rename="content.World")]
+	pub content_world: Stat,
+	#[serde(rename="content.Movement")]
+	pub content_movement: Stat,
+	#[serde(rename="content.Puzzles")]
+	pub content_puzzles: Stat,
+	#[serde(rename="content.Enemies")]
+	pub content_enemies: Stat,
+	#[serde(rename="content.Hazards")]
+	pub content_hazards: Stat,
+	#[serde(rename="stats.Attempts")]
+	pub stats_attempts: Option<Stat>,
+	#[serde(rename="stats.Favorites")]
+	pub stats_favorites: Option<Stat>,
+	#[serde(rename="stats.Likes")]
+	pub stats_likes: Option<Stat>,
+	#[serde(rename="stats.PerkPoints")]
+	pub stats_perk_points: Option<Stat>,
+	#[serde(rename="stats.PlayTime")]
+	pub stats_play_time: Option<Stat>,
+	#[serde(rename="stats.Players")]
+	pub stats_players: Option<Stat>,
+	#[serde(rename="stats.ReplayValue")]
+	pub stats_replay_value: Option<Stat>,
+	#[serde(rename="stats.ClearRate")]
+	pub stats_clear_rate: Option<f32>,
+	#[serde(rename="stats.Diamonds")]
+	pub stats_diamonds: Option<u8>,
+	#[serde(rename="stats.Successes")]
+	pub stats_successes: Option<Stat>,
+	#[serde(rename="stats.TimePerWin")]
+	pub stats_time_per_win: Option<f32>,
+	#[serde(rename="stats.ExposureBucks")]
+	pub stats_exposure_bucks: Option<Stat>,
+	#[serde(rename="stats.FailureRate")]
+	pub stats_failure_rate: Option<f32>,
+	#[cfg(feature="undocumented")]
+	#[serde(rename="stats.HiddenGem")]
+	pub stats_hidden_gem: Option<Stat>,
+	#[serde(rename="interactions.bookmarked")]
+	pub interactions_bookmarked: Option<bool>,
+	#[serde(rename="interactions.liked")]
+	pub interactions_liked: Option<bool>,
+	#[serde(rename="interactions.favorited")]
+	pub interactions_favorited: Option<bool>,
+}
+
+impl Export for Level {
+	type Row<'a> = LevelRow<'a>;
+	
+	fn row(&self) -> LevelRow<'_> {
+		let stats = self.stats.as_ref();
+		let interactions = self.interactions.as_ref();
+		LevelRow {
+			id: &self.id,
+			level_id: &self.level_id,
+			title: &self.title,
+			user_id: &self.user_id,
+			alias: alias_name(&self.alias),
+			cv: self.cv,
+			avatar_id: &self.avatar_id,
+			locale_id: self.locale_id,
+			locale: &self.locale,
+			created_at: &self.created_at,
+			updated_at: &self.updated_at,
+			tower: self.tower,
+			daily_build: self.daily_build,
+			tower_trial: self.tower_trial,
+			required_players: self.required_players,
+			creator_time: self.creator_time.get(),
+			game_version: self.game_version.as_deref(),
+			tags: self.tags.join(","),
+			tag_names: self.tag_names.join(","),
+			content_world: self.content.world,
+			content_movement: self.content.movement,
+			content_puzzles: self.content.puzzles,
+			content_enemies: self.content.enemies,
+			content_hazards: self.content.hazards,
+			stats_attempts: stats.map(|s| s.attempts),
+			stats_favorites: stats.map(|s| s.favorites),
+			stats_likes: stats.map(|s| s.likes),
+			stats_perk_points: stats.and_then(|s| s.perk_points),
+			stats_play_time: stats.map(|s| s.play_time),
+			stats_players: stats.map(|s| s.players),
+			stats_replay_value: stats.map(|s| s.replay_value),
+			stats_clear_rate: stats.map(|s| s.clear_rate.get()),
+			stats_diamonds: stats.map(|s| s.diamonds),
+			stats_successes: stats.map(|s| s.successes),
+			stats_time_per_win: stats.map(|s| s.time_per_win.get()),
+			stats_exposure_bucks: stats.map(|s| s.exposure_bucks),
+			stats_failure_rate: stats.map(|s| s.failure_rate.get()),
+			#[cfg(feature="undocumented")]
+			stats_hidden_gem: stats.map(|s| s.hidden_gem),
+			interactions_bookmarked: interactions.map(|i| i.bookmarked),
+			interactions_liked: interactions.map(|i| i.liked),
+			interactions_favorited: interactions.map(|i| i.favorited),
+		}
+	}
+}
+
+///A [Player] flattened into a single row
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlayerRow<'a> {
+	#[serde(rename="_id")]
+	pub id: &'a str,
+	#[serde(rename="userId")]
+	pub user_id: &'a str,
+	#[serde(rename="alias.alias")]
+	pub alias: Option<&'a str>,
+	#[serde(rename="alias.anonymous")]
+	pub alias_anonymous: Option<bool>,
+	#[serde(rename="createdAt")]
+	pub created_at: &'a str,
+	#[serde(rename="updatedAt")]
+	pub updated_at: &'a str,
+	#[serde(rename="stats.Subscribers")]
+	pub stats_subscribers: Stat,
+	#[serde(rename="stats.Published")]
+	pub stats_published: Stat,
+	#[serde(rename="stats.Plays")]
+	pub stats_plays: Stat,
+	#[serde(rename="stats.PlayTime")]
+	pub stats_play_time: Stat,
+	#[serde(rename="stats.Crowns")]
+	pub stats_crowns: Stat,
+	#[serde(rename="stats.Shoes")]
+	pub stats_shoes: Stat,
+	#[serde(rename="stats.LevelsPlayed")]
+	pub stats_levels_played: Stat,
+	#[serde(rename="stats.Wins")]
+	pub stats_wins: Stat,
+	#[serde(rename="stats.Fails")]
+	pub stats_fails: Stat,
+	#[serde(rename="stats.NumFollowing")]
+	pub stats_num_following: Stat,
+	#[serde(rename="stats.DBComp")]
+	pub stats_d_b_comp: Option<Stat>,
+	#[serde(rename="stats.ChalWins")]
+	pub stats_tower_trials: Stat,
+	#[serde(rename="stats.TimeTrophies")]
+	pub stats_time_trophies: Stat,
+	#[serde(rename="stats.FaveGen")]
+	pub stats_fave_gen: Stat,
+	#[serde(rename="stats.LikeGen")]
+	pub stats_like_gen: Stat,
+	#[serde(rename="stats.BucksTipped")]
+	pub stats_bucks_tipped: Stat,
+	#[serde(rename="stats.TipsGotten")]
+	pub stats_tips_gotten: Stat,
+	#[serde(rename="stats.AchPoints")]
+	pub stats_ach_points: Option<Stat>,
+	#[serde(rename="stats.CampaignProg")]
+	pub stats_campaign_progress: u8,
+	#[serde(rename="interactions.following")]
+	pub interactions_following: Option<bool>,
+}
+
+impl Export for Player {
+	type Row<'a> = PlayerRow<'a>;
+	
+	fn row(&self) -> PlayerRow<'_> {
+		let stats = &self.stats;
+		PlayerRow {
+			id: &self.id,
+			user_id: &self.user_id,
+			alias: alias_name(&self.alias),
+			alias_anonymous: self.alias.as_ref().and_then(|a| a.anonymous),
+			created_at: &self.created_at,
+			updated_at: &self.updated_at,
+			stats_subscribers: stats.subscribers,
+			stats_published: stats.published,
+			stats_plays: stats.plays,
+			stats_play_time: stats.play_time,
+			stats_crowns: stats.crowns,
+			stats_shoes: stats.shoes,
+			stats_levels_played: stats.levels_played,
+			stats_wins: stats.wins,
+			stats_fails: stats.fails,
+			stats_num_following: stats.num_following,
+			stats_d_b_comp: stats.d_b_comp,
+			stats_tower_trials: stats.tower_trials,
+			stats_time_trophies: stats.time_trophies,
+			stats_fave_gen: stats.fave_gen,
+			stats_like_gen: stats.like_gen,
+			stats_bucks_tipped: stats.bucks_tipped,
+			stats_tips_gotten: stats.tips_gotten,
+			stats_ach_points: stats.ach_points,
+			stats_campaign_progress: stats.campaign_progress,
+			interactions_following: self.interactions.as_ref().map(|i| i.following),
+		}
+	}
+}
+
+///The formats an [Exporter] can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+	///Comma separated values, starting with a header row
+	Csv,
+	///Newline delimited JSON, one object per row
+	Ndjson,
+}
+
+#[derive(Debug)]
+enum Sink<W: Write> {
+	Csv(Box<csv::Writer<W>>),
+	Ndjson(W),
+}
+
+///Writes entities as rows, see the [module docs](self)
+#[derive(Debug)]
+pub struct Exporter<W: Write, T> {
+	sink: Sink<W>,
+	written: usize,
+	item: PhantomData<fn(&T)>,
+}
+
+impl<W: Write, T: Export> Exporter<W, T> {
+	///Writes rows in the given format to `writer`. Wrap files in a [BufWriter](std::io::BufWriter) for NDJSON.
+	pub fn new(format: Format, writer: W) -> Self {
+		let sink = match format {
+			Format::Csv => Sink::Csv(Box::new(csv::Writer::from_writer(writer))),
+			Format::Ndjson => Sink::Ndjson(writer),
+		};
+		Self {
+			sink,
+			written: 0,
+			item: PhantomData,
+		}
+	}
+	
+	///Writes CSV. The header is written together with the first row, so exporting nothing results in an empty file.
+	pub fn csv(writer: W) -> Self {
+		Self::new(Format::Csv, writer)
+	}
+	
+	pub fn ndjson(writer: W) -> Self {
+		Self::new(Format::Ndjson, writer)
+	}
+	
+	///How many rows have been written
+	pub fn written(&self) -> usize {
+		self.written
+	}
+	
+	pub fn write(&mut self, item: &T) -> Result<(), ExportError> {
+		match &mut self.sink {
+			Sink::Csv(writer) => writer.serialize(item.row())?,
+			Sink::Ndjson(writer) => {
+				serde_json::to_writer(&mut *writer, &item.row())?;
+				writer.write_all(b"\n")?;
+			},
+		}
+		self.written += 1;
+		Ok(())
+	}
+	
+	pub fn write_all<'i>(&mut self, items: impl IntoIterator<Item = &'i T>) -> Result<(), ExportError>
+	where
+		T: 'i,
+	{
+		for item in items {
+			self.write(item)?;
+		}
+		Ok(())
+	}
+	
+	/**
+	Writes every page of a search as it arrives, returning how many rows were written.
+	
+	# Errors
+	Stops at the first page that failed, after writing all pages before it.
+	*/
+	pub async fn write_pages(&mut self, pages: impl Stream<Item = Result<Vec<T>, ClientError>>) -> Result<usize, ExportError> {
+		let mut pages = pin!(pages);
+		let start = self.written;
+		while let Some(page) = pages.next().await {
+			self.write_all(&page?)?;
+		}
+		Ok(self.written - start)
+	}
+	
+	///Like [Self::write_pages], but for the pages of a [blocking client](crate::blocking)
+	pub fn write_pages_blocking(&mut self, pages: impl IntoIterator<Item = Result<Vec<T>, ClientError>>) -> Result<usize, ExportError> {
+		let start = self.written;
+		for page in pages {
+			self.write_all(&page?)?;
+		}
+		Ok(self.written - start)
+	}
+	
+	///Flushes everything that was written, and returns the writer
+	pub fn finish(self) -> Result<W, ExportError> {
+		match self.sink {
+			Sink::Csv(writer) => writer.into_inner().map_err(|e| ExportError::Io(e.into_error())),
+			Sink::Ndjson(mut writer) => {
+				writer.flush()?;
+				Ok(writer)
+			},
+		}
+	}
+}
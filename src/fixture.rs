@@ -0,0 +1,115 @@
+/*!
+
+Made-up entities for tests, e.g. to fill a [mock](crate::mock) dataset.
+
+Everything is numbered, so ids, titles and dates follow from `n`.
+Change what a test needs with struct update syntax:
+
+```
+use rumpus_ce::{fixture, types::*};
+
+let level = Level {
+	tower: Some(false),
+	..fixture::level(3)
+};
+assert_eq!(level.level_id, "code03");
+```
+
+*/
+
+use super::types::*;
+
+///The ISO 8601 date levels get created on, `day` has to be from 1 up to 28
+pub fn level_date(day: u32) -> String {
+	format!("2022-02-{day:02}T12:00:00.000Z")
+}
+
+///Level `n` (`code03` for 3) by `user00`, created on day `n + 1`, with default stats and without records
+pub fn level(n: u32) -> Level {
+	level_on(n, n + 1)
+}
+
+///[level] `n`, but created on `day`, so several levels can be created at the same moment
+pub fn level_on(n: u32, day: u32) -> Level {
+	Level {
+		id: format!("level{n:02}"),
+		cv: 1,
+		level_id: format!("code{n:02}"),
+		user_id: "user00".into(),
+		alias: None,
+		avatar_id: "gr18-default".into(),
+		title: format!("Level {n}"),
+		locale_id: 1,
+		locale: "en-US".into(),
+		created_at: level_date(day),
+		updated_at: level_date(day),
+		tower: Some(true),
+		daily_build: None,
+		tower_trial: false,
+		required_players: 1,
+		creator_time: Float(10.0),
+		game_version: None,
+		tags: vec![],
+		tag_names: vec![],
+		content: LevelContents::default(),
+		stats: Some(LevelStats::default()),
+		records: None,
+		interactions: None,
+		extra: Extra::default(),
+	}
+}
+
+///The Levelhead alias `Player n` of `user_id`
+pub fn alias(user_id: &str, n: u32) -> Alias {
+	Alias {
+		user_id: user_id.into(),
+		alias_type: Some(AliasType::Levelhead),
+		alias: Some(format!("Player {n}")),
+		anonymous: None,
+		extra: Extra::default(),
+	}
+}
+
+///Player `n` (`user03` for 3) with their [alias], created on January `n + 1` 2020 and with default stats
+pub fn player(n: u32) -> Player {
+	let user_id = format!("user{n:02}");
+	Player {
+		id: format!("player{n:02}"),
+		alias: Some(alias(&user_id, n)),
+		user_id,
+		created_at: format!("2020-01-{:02}T00:00:00.000Z", n + 1),
+		updated_at: format!("2021-01-{:02}T00:00:00.000Z", n + 1),
+		interactions: None,
+		stats: PlayerStats::default(),
+		extra: Extra::default(),
+	}
+}
+
+///[player] `n` with `subscribers` and `crowns`, for rankings and milestones
+pub fn player_with(n: u32, subscribers: Stat, crowns: Stat) -> Player {
+	Player {
+		stats: PlayerStats { subscribers, crowns, ..Default::default() },
+		..player(n)
+	}
+}
+
+///A leaderboard record by `user_id`
+pub fn record(user_id: &str, value: f32) -> Record {
+	Record {
+		user_id: user_id.into(),
+		alias: None,
+		value: Float(value),
+		created_at: "2022-03-01T12:00:00.000Z".into(),
+		extra: Extra::default(),
+	}
+}
+
+///A delegation key of `user_id` without permissions
+pub fn key_info(user_id: &str) -> DelegationKeyInfo {
+	DelegationKeyInfo {
+		user_id: user_id.into(),
+		pass_id: "pass".into(),
+		permissions: Default::default(),
+		extra: Extra::default(),
+	}
+}
@@ -0,0 +1,17 @@
+///FNV-1a, a hash that is stable across Rust versions, unlike the std one
+pub(crate) fn fnv1a(s: &str) -> u64 {
+	s.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn known_values() {
+		assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+		assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+	}
+}
\ No newline at end of file
@@ -0,0 +1,344 @@
+/*!
+
+Loading delegation keys and checking they work.
+
+A [KeySource] says where to find a [DelegationKey]: an environment variable, a file, or a config directory.
+The [default](KeySource::default) tries the `RUMPUS_DELEGATION_KEY` environment variable,
+then the `delegation-key` file in the [config directory](KeySource::default_config_dir).
+With the `keyring` feature, keys can also be read from the [system keyring](KeySource::Keyring).
+Validating a key asks Rumpus which user it belongs to and what it's allowed to do,
+so a bad key is noticed on startup instead of on the first real request:
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::key::KeyError> {
+use rumpus_ce::{client::RumpusClient, key::KeySource};
+
+let validated = KeySource::default().load_validated(rumpus_ce::API_URL).await?;
+println!("{validated}");
+let client = RumpusClient::with_key(validated.key.expose())?;
+# Ok(())
+# }
+```
+
+[DelegationKey] never shows the key in its [Debug] and [Display](fmt::Display) output,
+only its last few characters, so it can be logged safely.
+Use [DelegationKey::expose] to get the actual key.
+
+*/
+
+use std::{
+	env, fmt, fs, io,
+	path::PathBuf,
+};
+use thiserror::Error;
+use super::{
+	client::{ClientError, RumpusClient},
+	hash::fnv1a,
+	types::DelegationKeyInfo,
+};
+
+#[derive(Error, Debug)]
+pub enum KeyError {
+	#[error("no delegation key found, tried {tried}")]
+	Missing {
+		tried: String,
+	},
+	#[error("{from} is empty")]
+	Empty {
+		from: String,
+	},
+	#[error("{from} isn't valid unicode")]
+	NotUnicode {
+		from: String,
+	},
+	#[error("couldn't read {path}: {source}")]
+	Io {
+		path: PathBuf,
+		source: io::Error,
+	},
+	///Rumpus doesn't accept the key
+	#[error("delegation key {key} was rejected with status {status}")]
+	Rejected {
+		key: DelegationKey,
+		status: u16,
+	},
+	#[error(transparent)]
+	Client(#[from] ClientError),
+	///The system keyring couldn't be read
+	#[cfg(feature="keyring")]
+	#[error("couldn't read {from}: {source}")]
+	Keyring {
+		from: String,
+		source: keyring::Error,
+	},
+}
+
+///A delegation key, which doesn't show itself when printed
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DelegationKey(String);
+
+impl DelegationKey {
+	///How many characters at the end of the key are shown when printing it
+	const SHOWN: usize = 4;
+	
+	///Whitespace around the key is removed
+	pub fn new(key: impl Into<String>) -> Self {
+		let key = key.into();
+		Self(key.trim().to_owned())
+	}
+	
+	///The actual key, careful where you put it
+	pub fn expose(&self) -> &str {
+		&self.0
+	}
+	
+	///A hash of the key, to tell keys apart without storing them. Stays the same across versions, but isn't cryptographically secure.
+	pub fn fingerprint(&self) -> String {
+		format!("{:016x}", fnv1a(&self.0))
+	}
+	
+	/**
+	Asks Rumpus at `url` about this key.
+	
+	# Errors
+	With [KeyError::Rejected] if Rumpus doesn't accept the key.
+	*/
+	pub async fn validate(&self, url: impl AsRef<str>) -> Result<DelegationKeyInfo, KeyError> {
+		let client = RumpusClient::with_key_url(self.expose(), url)?;
+		match client.key_info().await {
+			Ok(info) => Ok(info),
+			Err(ClientError::Transport(err)) if matches!(err.status(), Some(401 | 403)) => Err(KeyError::Rejected {
+				key: self.clone(),
+				status: err.status().unwrap_or_default(),
+			}),
+			Err(err) => Err(err.into()),
+		}
+	}
+}
+
+impl fmt::Display for DelegationKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let chars = self.0.chars().count();
+		//Short keys would be shown almost entirely
+		if chars < Self::SHOWN * 3 {
+			return write!(f, "****");
+		}
+		let shown = self.0.chars().skip(chars - Self::SHOWN).collect::<String>();
+		write!(f, "****{shown}")
+	}
+}
+
+impl fmt::Debug for DelegationKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "DelegationKey({self})")
+	}
+}
+
+///A key Rumpus accepted, with what it told about it
+#[derive(Debug, Clone)]
+pub struct ValidatedKey {
+	pub key: DelegationKey,
+	pub info: DelegationKeyInfo,
+}
+
+impl fmt::Display for ValidatedKey {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut permissions = self.info.permissions.iter().map(ToString::to_string).collect::<Vec<_>>();
+		permissions.sort_unstable();
+		if permissions.is_empty() {
+			permissions.push("nothing".into());
+		}
+		write!(f, "delegation key {} of {}, allowed: {}", self.key, self.info.user_id, permissions.join(", "))
+	}
+}
+
+///Where to load a delegation key from, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+	///A key you already have
+	Key(DelegationKey),
+	///The environment variable with this name
+	Env(String),
+	///A file containing only the key
+	File(PathBuf),
+	///The [file](KeySource::FILE_NAME) containing only the key in this directory
+	ConfigDir(PathBuf),
+	///The entry for this user in the [service](KeySource::KEYRING_SERVICE) of the system keyring
+	#[cfg(feature="keyring")]
+	Keyring(String),
+	///The first of these sources that has a key
+	FirstOf(Vec<KeySource>),
+}
+
+impl Default for KeySource {
+	fn default() -> Self {
+		let mut sources = vec![Self::env()];
+		sources.extend(Self::default_config_dir().map(Self::ConfigDir));
+		Self::FirstOf(sources)
+	}
+}
+
+impl KeySource {
+	///The environment variable [KeySource::env] reads
+	pub const ENV_VAR: &'static str = "RUMPUS_DELEGATION_KEY";
+	///The name of the file [KeySource::ConfigDir] reads
+	pub const FILE_NAME: &'static str = "delegation-key";
+	///The service [KeySource::Keyring] reads entries of
+	#[cfg(feature="keyring")]
+	pub const KEYRING_SERVICE: &'static str = "rumpus";
+	
+	pub fn key(key: impl Into<String>) -> Self {
+		Self::Key(DelegationKey::new(key))
+	}
+	
+	///The [ENV_VAR](Self::ENV_VAR) environment variable
+	pub fn env() -> Self {
+		Self::Env(Self::ENV_VAR.into())
+	}
+	
+	pub fn file(path: impl Into<PathBuf>) -> Self {
+		Self::File(path.into())
+	}
+	
+	#[cfg(feature="keyring")]
+	pub fn keyring(user: impl Into<String>) -> Self {
+		Self::Keyring(user.into())
+	}
+	
+	/**
+	The `rumpus` directory in the user's config directory:
+	`$XDG_CONFIG_HOME` or `~/.config` on Unix, `%APPDATA%` on Windows.
+	
+	`None` if the environment doesn't say where that is.
+	*/
+	pub fn default_config_dir() -> Option<PathBuf> {
+		let base = if cfg!(windows) {
+			env::var_os("APPDATA").map(PathBuf::from)
+		} else {
+			env::var_os("XDG_CONFIG_HOME")
+				.filter(|dir| !dir.is_empty())
+				.map(PathBuf::from)
+				.or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+		};
+		base.map(|dir| dir.join("rumpus"))
+	}
+	
+	/**
+	Loads the key.
+	
+	# Errors
+	- [KeyError::Missing] if the source doesn't exist
+	- [KeyError::Empty] if it exists, but contains no key
+	- [KeyError::NotUnicode] if an environment variable exists, but isn't valid unicode
+	- [KeyError::Io] if a file exists, but couldn't be read
+	- `KeyError::Keyring` if the keyring couldn't be read
+	*/
+	pub fn load(&self) -> Result<DelegationKey, KeyError> {
+		let (key, from) = match self {
+			Self::Key(key) => (key.expose().to_owned(), self.to_string()),
+			Self::Env(var) => match env::var(var) {
+				Ok(key) => (key, self.to_string()),
+				Err(env::VarError::NotPresent) => return Err(KeyError::Missing { tried: self.to_string() }),
+				Err(env::VarError::NotUnicode(_)) => return Err(KeyError::NotUnicode { from: self.to_string() }),
+			},
+			Self::File(path) => (read(path.clone())?, self.to_string()),
+			Self::ConfigDir(dir) => (read(dir.join(Self::FILE_NAME))?, self.to_string()),
+			#[cfg(feature="keyring")]
+			Self::Keyring(user) => {
+				let key = keyring::Entry::new(Self::KEYRING_SERVICE, user)
+					.and_then(|entry| entry.get_password());
+				match key {
+					Ok(key) => (key, self.to_string()),
+					Err(keyring::Error::NoEntry) => return Err(KeyError::Missing { tried: self.to_string() }),
+					Err(source) => return Err(KeyError::Keyring { from: self.to_string(), source }),
+				}
+			},
+			Self::FirstOf(sources) => {
+				for source in sources {
+					match source.load() {
+						Err(KeyError::Missing { .. }) => continue,
+						result => return result,
+					}
+				}
+				return Err(KeyError::Missing { tried: self.to_string() });
+			},
+		};
+		
+		let key = DelegationKey::new(key);
+		if key.expose().is_empty() {
+			return Err(KeyError::Empty { from });
+		}
+		Ok(key)
+	}
+	
+	///Loads the key and [validates](DelegationKey::validate) it against the API at `url`
+	pub async fn load_validated(&self, url: impl AsRef<str>) -> Result<ValidatedKey, KeyError> {
+		let key = self.load()?;
+		let info = key.validate(url).await?;
+		Ok(ValidatedKey { key, info })
+	}
+}
+
+fn read(path: PathBuf) -> Result<String, KeyError> {
+	match fs::read_to_string(&path) {
+		Ok(key) => Ok(key),
+		Err(err) if err.kind() == io::ErrorKind::NotFound => Err(KeyError::Missing { tried: path.display().to_string() }),
+		Err(source) => Err(KeyError::Io { path, source }),
+	}
+}
+
+impl fmt::Display for KeySource {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Key(_) => write!(f, "the given key"),
+			Self::Env(var) => write!(f, "environment variable {var}"),
+			Self::File(path) => write!(f, "{}", path.display()),
+			Self::ConfigDir(dir) => write!(f, "{}", dir.join(Self::FILE_NAME).display()),
+			#[cfg(feature="keyring")]
+			Self::Keyring(user) => write!(f, "keyring entry {user} of {}", Self::KEYRING_SERVICE),
+			Self::FirstOf(sources) => {
+				for (i, source) in sources.iter().enumerate() {
+					if i != 0 {
+						write!(f, ", ")?;
+					}
+					write!(f, "{source}")?;
+				}
+				Ok(())
+			},
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn redacts() {
+		let key = DelegationKey::new(" abcdefghijklmnop\n");
+		assert_eq!(key.expose(), "abcdefghijklmnop");
+		assert_eq!(key.to_string(), "****mnop");
+		assert_eq!(format!("{key:?}"), "DelegationKey(****mnop)");
+		assert_eq!(format!("{:?}", KeySource::Key(key)), "Key(DelegationKey(****mnop))");
+		assert_eq!(DelegationKey::new("short").to_string(), "****");
+	}
+	
+	#[test]
+	fn fingerprint() {
+		//Used as the scope of cached responses, so it can't change between versions
+		assert_eq!(DelegationKey::new("abcdefghijklmnop").fingerprint(), "7ef46f6c05086855");
+	}
+	
+	#[cfg(feature="keyring")]
+	#[test]
+	fn keyring() {
+		//The mock keyring has no entries, so this doesn't touch the real keyring
+		keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+		let Err(KeyError::Missing { tried }) = KeySource::keyring("someone").load() else {
+			panic!("found a key that doesn't exist");
+		};
+		assert_eq!(tried, "keyring entry someone of rumpus");
+	}
+}
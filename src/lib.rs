@@ -4,11 +4,42 @@ This crate tries to provide an interface to the [Rumpus CE API](https://www.bsco
 
 It is build on [restson](https://crates.io/crates/restson), which is build on [hyper](https://crates.io/crates/hyper) and [tokio](https://crates.io/crates/tokio)
 
+The [client::RumpusClient] is the easiest way to use it.
+It sends its requests through a [pluggable transport](transport), which uses restson by default.
 See the RestPath implementations for the [types::Rumpus#trait-implementations] type to see which endpoints you can currently use.
 
 It provides some support for undocumented data through the `undocumented` feature, which is enabled by default.
 It can be turned off in case Rumpus CE makes some changes to it's undocumented parts.
 
+The `mock` feature provides a [local server](mock) that serves data like Rumpus CE does, for testing without hitting the real API.
+The `replay` feature can [record responses and replay them](replay) later.
+The `blocking` feature provides a [synchronous client](blocking).
+The `sqlite-cache` feature can [persist fetched data](sqlite_cache) in a local SQLite database.
+The `export` feature can [export levels and players](export) as CSV or newline delimited JSON,
+and the `arrow` feature can convert them [to Arrow and Parquet](arrow).
+
+The `tracing` feature emits a `rumpus_request` span at the debug level for every request the [client](client::RumpusClient) makes,
+with an event when it's done: debug if it succeeded or was served from the cache, warn if it failed.
+The span has these fields, some of which are only filled in when they apply:
+
+| Field | |
+|-|-|
+| `endpoint` | The path of the endpoint, e.g. `levelhead/levels` |
+| `query` | The query string |
+| `cache` | `hit` or `miss`, if the client has a [response cache](cache) |
+| `status` | The HTTP status of the last response, `200` for every successful one |
+| `latency_ms` | How long the transport took, in milliseconds |
+| `key` | The [redacted](key::DelegationKey) delegation key the request was sent with |
+| `remaining` | How many requests the key had left in its [pool](pool) |
+| `retries` | How often the [pool](pool) retried the request with a different key, the responses it retried get a warning |
+| `retry` | Which retry of the [crawler](crawl) the request is, after it got rate limited or a server error |
+
+The [pool](pool) and the [crawler](crawl) also emit warnings when keys get rate limited or revoked, and when they retry.
+
+The `metrics` feature records [counters and histograms](metrics) through the [metrics](https://crates.io/crates/metrics) facade.
+
+The `keyring` feature can read delegation keys from the [system keyring](key::KeySource::Keyring).
+
 */
 #![allow(clippy::tabs_in_doc_comments)]
 
@@ -18,14 +49,64 @@ pub mod types;
 pub mod query;
 ///Handles integrating the right URL and header with the rets client
 pub mod rest_client;
+///The HTTP layer requests are sent through
+pub mod transport;
+///Switching between the production and beta API
+pub mod environment;
+///Loading and validating delegation keys
+pub mod key;
+///Spreading requests over several delegation keys
+pub mod pool;
+///High-level client
+pub mod client;
+///Caching responses in memory
+pub mod cache;
+///Persisting fetched entities in SQLite
+#[cfg(feature="sqlite-cache")]
+pub mod sqlite_cache;
+///Archiving everything a search returns
+pub mod crawl;
+///Tracking how stats change over time
+pub mod snapshots;
+///Watching for new levels and stat changes
+pub mod watch;
+///Exporting levels and players as CSV or newline delimited JSON
+#[cfg(feature="export")]
+pub mod export;
+///Converting levels and players to Arrow and Parquet
+#[cfg(feature="arrow")]
+pub mod arrow;
+///Counters and histograms for monitoring clients
+#[cfg(feature="metrics")]
+pub mod metrics;
+///Synchronous version of the high-level client
+#[cfg(feature="blocking")]
+pub mod blocking;
+///Collecting the leaderboard records players hold
+pub mod records;
+///Noticing and handling fields Rumpus returns that we don't know about
+pub mod unknown;
+///A local server mimicking Rumpus CE for offline testing
+#[cfg(feature="mock")]
+pub mod mock;
+///Made-up levels and players for tests
+#[cfg(any(test, feature="mock"))]
+pub mod fixture;
+///Recording and replaying responses for offline testing
+#[cfg(feature="replay")]
+pub mod replay;
 
 ///Restson restPath implementations
 mod endpoints;
+///Instrumenting requests, for the `tracing` and `metrics` features
+mod trace;
+///Hashes that don't change between Rust versions
+mod hash;
 
 
 //Restson requires the https:// for some reason
 
-///The URL of the API
+///The URL of the API, see also [Environment](environment::Environment)
 pub const API_URL: &str = "https://www.bscotch.net/api/";
-///The URL of the beta API
+///The URL of the beta API, see also [Environment](environment::Environment)
 pub const BETA_URL: &str = "https://beta.bscotch.net/api/";
\ No newline at end of file
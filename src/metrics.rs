@@ -0,0 +1,149 @@
+/*!
+
+Counters and histograms for monitoring clients, through the [metrics](https://crates.io/crates/metrics) facade.
+
+Nothing gets recorded until a recorder (exporter) is installed, e.g. one from `metrics-exporter-prometheus`.
+Call [describe] after installing it to give the metrics descriptions and units.
+
+| Metric | Type | Labels | |
+|-|-|-|-|
+| [`rumpus_requests_total`](REQUESTS) | counter | `endpoint` | HTTP requests sent, including the ones a [key pool](crate::pool) retried with another key |
+| [`rumpus_errors_total`](ERRORS) | counter | `endpoint`, `kind` | Failed requests as the client saw them, see [ErrorKind] for the kinds |
+| [`rumpus_received_bytes_total`](RECEIVED_BYTES) | counter | `endpoint` | Bytes in the bodies of successful responses |
+| [`rumpus_request_duration_seconds`](REQUEST_DURATION) | histogram | `endpoint` | How long the transport took |
+| [`rumpus_cache_hits_total`](CACHE_HITS) | counter | `endpoint` | Responses served from the [response cache](crate::cache) |
+| [`rumpus_cache_misses_total`](CACHE_MISSES) | counter | `endpoint` | Responses not in the response cache, only counted if the client has one |
+| [`rumpus_rate_limit_waits_total`](RATE_LIMIT_WAITS) | counter | `source` | Times a [key pool](crate::pool) or a [crawler](crate::crawl) waited because of the rate limit |
+| [`rumpus_rate_limit_wait_seconds`](RATE_LIMIT_WAIT) | histogram | `source` | How long those waits were |
+
+`endpoint` is the [name](Endpoint::name) of the endpoint, e.g. `levels`, and `source` is either `pool` or `crawl`.
+The cache hit ratio is `rumpus_cache_hits_total / (rumpus_cache_hits_total + rumpus_cache_misses_total)`.
+
+*/
+
+use std::time::Duration;
+use ::metrics::{Unit, counter, describe_counter, describe_histogram, histogram};
+use super::{
+	cache::Endpoint,
+	client::ClientError,
+	pool::PoolError,
+	transport::TransportError,
+};
+
+pub const REQUESTS: &str = "rumpus_requests_total";
+pub const ERRORS: &str = "rumpus_errors_total";
+pub const RECEIVED_BYTES: &str = "rumpus_received_bytes_total";
+pub const REQUEST_DURATION: &str = "rumpus_request_duration_seconds";
+pub const CACHE_HITS: &str = "rumpus_cache_hits_total";
+pub const CACHE_MISSES: &str = "rumpus_cache_misses_total";
+pub const RATE_LIMIT_WAITS: &str = "rumpus_rate_limit_waits_total";
+pub const RATE_LIMIT_WAIT: &str = "rumpus_rate_limit_wait_seconds";
+
+///Registers the descriptions and units of all metrics with the installed recorder
+pub fn describe() {
+	describe_counter!(REQUESTS, "Requests sent to Rumpus CE");
+	describe_counter!(ERRORS, "Requests to Rumpus CE that failed");
+	describe_counter!(RECEIVED_BYTES, Unit::Bytes, "Bytes received from Rumpus CE");
+	describe_histogram!(REQUEST_DURATION, Unit::Seconds, "How long requests to Rumpus CE took");
+	describe_counter!(CACHE_HITS, "Responses served from the response cache");
+	describe_counter!(CACHE_MISSES, "Responses not found in the response cache");
+	describe_counter!(RATE_LIMIT_WAITS, "Times requests waited because of the rate limit");
+	describe_histogram!(RATE_LIMIT_WAIT, Unit::Seconds, "How long requests waited because of the rate limit");
+}
+
+///The `kind` label of [ERRORS]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+	///HTTP 429
+	RateLimited,
+	///HTTP 401 or 403, or every key in a [pool](crate::pool) got revoked
+	Rejected,
+	///HTTP 5xx
+	Server,
+	///Any other HTTP status
+	Status,
+	///The request couldn't be made or the response couldn't be received
+	Transport,
+	///The response couldn't be parsed
+	Parse,
+	///The [field policy](crate::unknown::FieldPolicy) rejected the response
+	UnknownFields,
+}
+
+impl ErrorKind {
+	pub fn name(self) -> &'static str {
+		match self {
+			Self::RateLimited => "rate_limited",
+			Self::Rejected => "rejected",
+			Self::Server => "server",
+			Self::Status => "status",
+			Self::Transport => "transport",
+			Self::Parse => "parse",
+			Self::UnknownFields => "unknown_fields",
+		}
+	}
+	
+	fn of_transport(error: &TransportError) -> Self {
+		match error {
+			TransportError::Status { status: 429, .. } => Self::RateLimited,
+			TransportError::Status { status: 401 | 403, .. } => Self::Rejected,
+			TransportError::Status { status: 500..=599, .. } => Self::Server,
+			TransportError::Status { .. } => Self::Status,
+			//The pool turns the rejections of its keys into this
+			TransportError::Other(error) if matches!(error.downcast_ref(), Some(PoolError::AllRevoked | PoolError::PinnedRevoked)) => Self::Rejected,
+			TransportError::Other(_) => Self::Transport,
+		}
+	}
+	
+	///`None` for errors that aren't about the response
+	pub fn of(error: &ClientError) -> Option<Self> {
+		match error {
+			ClientError::Transport(error) => Some(Self::of_transport(error)),
+			ClientError::Parse { .. } => Some(Self::Parse),
+			ClientError::UnknownFields(_) => Some(Self::UnknownFields),
+			_ => None,
+		}
+	}
+}
+
+pub(crate) fn request(endpoint: Endpoint, duration: Duration, result: &Result<String, TransportError>) {
+	let endpoint = endpoint.name();
+	//A pool that ran out of keys didn't send anything more, it records what it did send with [failover]
+	let sent = !matches!(result, Err(TransportError::Other(error)) if error.downcast_ref::<PoolError>().is_some());
+	if sent {
+		counter!(REQUESTS, "endpoint" => endpoint).increment(1);
+		histogram!(REQUEST_DURATION, "endpoint" => endpoint).record(duration);
+	}
+	match result {
+		Ok(body) => counter!(RECEIVED_BYTES, "endpoint" => endpoint).increment(body.len() as u64),
+		Err(error) => error_kind(endpoint, ErrorKind::of_transport(error)),
+	}
+}
+
+///Records a request a [pool](crate::pool) retried with another key, so the client never saw its response
+pub(crate) fn failover(endpoint: Endpoint, duration: Duration) {
+	let endpoint = endpoint.name();
+	counter!(REQUESTS, "endpoint" => endpoint).increment(1);
+	histogram!(REQUEST_DURATION, "endpoint" => endpoint).record(duration);
+}
+
+///Records errors the client ran into after getting a response, [request] already records transport errors
+pub(crate) fn error(endpoint: Endpoint, error: &ClientError) {
+	if let (Some(kind), false) = (ErrorKind::of(error), matches!(error, ClientError::Transport(_))) {
+		error_kind(endpoint.name(), kind);
+	}
+}
+
+fn error_kind(endpoint: &'static str, kind: ErrorKind) {
+	counter!(ERRORS, "endpoint" => endpoint, "kind" => kind.name()).increment(1);
+}
+
+pub(crate) fn cache(endpoint: Endpoint, hit: bool) {
+	let name = if hit { CACHE_HITS } else { CACHE_MISSES };
+	counter!(name, "endpoint" => endpoint.name()).increment(1);
+}
+
+pub(crate) fn rate_limit_wait(source: &'static str, wait: Duration) {
+	counter!(RATE_LIMIT_WAITS, "source" => source).increment(1);
+	histogram!(RATE_LIMIT_WAIT, "source" => source).record(wait);
+}
@@ -0,0 +1,694 @@
+/*!
+
+An in-process HTTP server that mimics the Rumpus CE API, serving from an in-memory [Dataset].
+
+It implements the endpoints this crate supports (levels, players, aliases and delegation key info),
+including their filters, sorting, limits and tiebreaker paging, so tests can run offline and deterministically.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::mock::MockError> {
+use rumpus_ce::{mock::{Dataset, MockServer}, types::*, query::*};
+
+let key = "test-key";
+let dataset = Dataset::new()
+	.key(key, DelegationKeyInfo {
+		user_id: "m7n6j8".into(),
+		pass_id: "pass".into(),
+		permissions: Default::default(),
+		extra: Default::default(),
+	});
+
+let server = MockServer::start(dataset).await?;
+let players = server.client(key)
+	.get::<_, Rumpus<Vec<Player>>>(PlayerSearch::new())
+	.await;
+# Ok(())
+# }
+```
+
+Some things the mock can't know from the data it has, and handles like this:
+- `marketing` levels are those that are neither tower nor daily build levels
+- `includeBeta` is ignored, all levels are considered playable on release clients
+- Dates have to be ISO 8601 in UTC (e.g. `2023-04-01T12:00:00.000Z`), instead of anything Javascript can parse
+
+*/
+
+use std::{
+	cmp::Ordering,
+	collections::HashMap,
+	convert::Infallible,
+	net::SocketAddr,
+	str::FromStr,
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use hyper::{
+	header::CONTENT_TYPE,
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode,
+};
+use restson::RestClient;
+use thiserror::Error;
+use tokio::sync::oneshot;
+use super::{
+	query::*,
+	rest_client,
+	types::*,
+};
+
+///The data a [MockServer] serves
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+	pub players: Vec<Player>,
+	pub levels: Vec<Level>,
+	///The delegation keys that are accepted, and the info returned about them
+	pub keys: HashMap<String, DelegationKeyInfo>,
+}
+
+impl Dataset {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	pub fn player(mut self, player: Player) -> Self {
+		self.players.push(player);
+		self
+	}
+	
+	pub fn level(mut self, level: Level) -> Self {
+		self.levels.push(level);
+		self
+	}
+	
+	pub fn players(mut self, players: impl IntoIterator<Item = Player>) -> Self {
+		self.players.extend(players);
+		self
+	}
+	
+	pub fn levels(mut self, levels: impl IntoIterator<Item = Level>) -> Self {
+		self.levels.extend(levels);
+		self
+	}
+	
+	pub fn key(mut self, key: impl Into<String>, info: DelegationKeyInfo) -> Self {
+		self.keys.insert(key.into(), info);
+		self
+	}
+}
+
+#[derive(Error, Debug)]
+pub enum MockError {
+	#[error("couldn't start the mock server: {0}")]
+	Bind(#[from] hyper::Error),
+}
+
+/**
+A running mock Rumpus CE server, listening on a random local port.
+
+The server is shut down when this is dropped.
+*/
+#[derive(Debug)]
+pub struct MockServer {
+	address: SocketAddr,
+	shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+	/**
+	Starts serving the dataset on a random port on localhost.
+	
+	Has to be called from within a Tokio runtime, which the server will run on.
+	
+	# Errors
+	If the server couldn't bind to a port.
+	*/
+	pub async fn start(dataset: Dataset) -> Result<Self, MockError> {
+		let dataset = Arc::new(dataset);
+		let make_service = make_service_fn(move |_| {
+			let dataset = dataset.clone();
+			async move {
+				Ok::<_, Infallible>(service_fn(move |request| handle(dataset.clone(), request)))
+			}
+		});
+		
+		let server = Server::try_bind(&SocketAddr::from(([127, 0, 0, 1], 0)))?
+			.serve(make_service);
+		let address = server.local_addr();
+		
+		let (shutdown, shutdown_signal) = oneshot::channel::<()>();
+		let server = server.with_graceful_shutdown(async {
+			shutdown_signal.await.ok();
+		});
+		tokio::spawn(server);
+		
+		Ok(Self {
+			address,
+			shutdown: Some(shutdown),
+		})
+	}
+	
+	pub fn address(&self) -> SocketAddr {
+		self.address
+	}
+	
+	///The URL of the API, to be used instead of [API_URL](crate::API_URL)
+	pub fn url(&self) -> String {
+		format!("http://{}/api/", self.address)
+	}
+	
+	///Creates a [RestClient] that talks to this server using the given key
+	pub fn client(&self, key: impl AsRef<str>) -> RestClient {
+		rest_client::with_key_url(key, self.url())
+	}
+}
+
+impl Drop for MockServer {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			//Fails if the server already stopped, which is fine
+			shutdown.send(()).ok();
+		}
+	}
+}
+
+
+
+///Default amount of results when no limit is set
+const DEFAULT_LIMIT: usize = 64;
+
+///A response other than a success
+struct Failure {
+	status: StatusCode,
+	message: String,
+}
+
+impl Failure {
+	fn new(status: StatusCode, message: impl Into<String>) -> Self {
+		Self {
+			status,
+			message: message.into(),
+		}
+	}
+	
+	fn bad_request(message: impl Into<String>) -> Self {
+		Self::new(StatusCode::BAD_REQUEST, message)
+	}
+}
+
+async fn handle(dataset: Arc<Dataset>, request: Request<Body>) -> Result<Response<Body>, Infallible> {
+	let (status, body) = match route(&dataset, &request) {
+		Ok(body) => (StatusCode::OK, body),
+		Err(failure) => {
+			let body = Rumpus::<()> {
+				data: None,
+				meta: None,
+				message: Some(failure.message),
+				location: None,
+				extra: Extra::default(),
+			};
+			(failure.status, to_json(&body))
+		},
+	};
+	
+	let response = Response::builder()
+		.status(status)
+		.header(CONTENT_TYPE, "application/json")
+		.body(Body::from(body))
+		.expect("response parts are valid");
+	Ok(response)
+}
+
+fn to_json(value: &impl serde::Serialize) -> String {
+	serde_json::to_string(value).expect("Rumpus types serialize to JSON")
+}
+
+fn data<D: serde::Serialize>(data: D) -> String {
+	to_json(&Rumpus {
+		data: Some(data),
+		//Real responses always have one
+		meta: Some(Extra::default()),
+		message: None,
+		location: None,
+		extra: Extra::default(),
+	})
+}
+
+fn route(dataset: &Dataset, request: &Request<Body>) -> Result<String, Failure> {
+	if request.method() != Method::GET {
+		return Err(Failure::new(StatusCode::METHOD_NOT_ALLOWED, "only GET requests are supported"));
+	}
+	
+	let key_info = request.headers()
+		.get(rest_client::KEY_HEADER)
+		.and_then(|key| key.to_str().ok())
+		.and_then(|key| dataset.keys.get(key))
+		.ok_or_else(|| Failure::new(StatusCode::UNAUTHORIZED, "invalid or missing delegation key"))?;
+	
+	let params = Params::parse(request.uri().query().unwrap_or(""));
+	
+	match request.uri().path() {
+		"/api/delegation/keys/@this" => Ok(data(key_info)),
+		"/api/levelhead/players" => Ok(data(players(dataset, &params)?)),
+		"/api/levelhead/levels" => Ok(data(levels(dataset, &params)?)),
+		"/api/levelhead/aliases" => Ok(data(aliases(dataset, &params)?)),
+		path => Err(Failure::new(StatusCode::NOT_FOUND, format!("{path} is not a mocked endpoint"))),
+	}
+}
+
+///Query string parameters
+struct Params(HashMap<String, String>);
+
+impl Params {
+	fn parse(query: &str) -> Self {
+		Self(url::form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+	}
+	
+	fn get<T: FromStr>(&self, name: &str) -> Result<Option<T>, Failure> {
+		self.0.get(name)
+			.map(|v| v.parse().map_err(|_| Failure::bad_request(format!("invalid value {v:?} for {name}"))))
+			.transpose()
+	}
+	
+	fn flag(&self, name: &str) -> Result<bool, Failure> {
+		Ok(self.get(name)?.unwrap_or(false))
+	}
+	
+	fn list(&self, name: &str, maximum: usize) -> Result<Option<Vec<String>>, Failure> {
+		let Some(v) = self.0.get(name) else {
+			return Ok(None);
+		};
+		let list = v.split(',').map(String::from).collect::<Vec<_>>();
+		if list.len() > maximum {
+			return Err(Failure::bad_request(format!("{name} can contain at most {maximum} items")));
+		}
+		Ok(Some(list))
+	}
+	
+	fn limit(&self, maximum: usize) -> Result<usize, Failure> {
+		let limit = self.get::<usize>("limit")?.unwrap_or(DEFAULT_LIMIT);
+		if limit > maximum {
+			return Err(Failure::bad_request(format!("limit can be at most {maximum}")));
+		}
+		Ok(limit)
+	}
+	
+	fn timestamp(&self, name: &str) -> Result<Option<i64>, Failure> {
+		self.0.get(name)
+			.map(|v| parse_timestamp(v).ok_or_else(|| Failure::bad_request(format!("invalid date {v:?} for {name}"))))
+			.transpose()
+	}
+	
+	///Checks `value` against the `min{name}` and `max{name}` parameters
+	fn in_range<T: FromStr + PartialOrd>(&self, name: &str, value: T) -> Result<bool, Failure> {
+		let above_min = self.get::<T>(&format!("min{name}"))?.is_none_or(|min| value >= min);
+		let below_max = self.get::<T>(&format!("max{name}"))?.is_none_or(|max| value <= max);
+		Ok(above_min && below_max)
+	}
+	
+	fn has_range(&self, name: &str) -> bool {
+		self.0.contains_key(&format!("min{name}")) || self.0.contains_key(&format!("max{name}"))
+	}
+	
+	fn in_time_range(&self, name: &str, timestamp: &str) -> Result<bool, Failure> {
+		let min = self.timestamp(&format!("min{name}"))?;
+		let max = self.timestamp(&format!("max{name}"))?;
+		if min.is_none() && max.is_none() {
+			return Ok(true);
+		}
+		let Some(time) = parse_timestamp(timestamp) else {
+			return Ok(false);
+		};
+		Ok(min.is_none_or(|min| time >= min) && max.is_none_or(|max| time <= max))
+	}
+}
+
+/**
+Parses `YYYY-MM-DDTHH:MM:SS(.sss)Z` into milliseconds since the Unix epoch.
+
+Uses the days from civil algorithm from <http://howardhinnant.github.io/date_algorithms.html>
+*/
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+	let timestamp = timestamp.strip_suffix('Z')?;
+	let (date, time) = timestamp.split_once('T')?;
+	
+	let mut date = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+	let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+	
+	let (time, millis) = match time.split_once('.') {
+		Some((time, fraction)) => {
+			let digits = fraction.get(..3.min(fraction.len()))?;
+			let millis = digits.parse::<i64>().ok()? * 10_i64.pow(3 - digits.len() as u32);
+			(time, millis)
+		},
+		None => (time, 0),
+	};
+	let mut time = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+	let (hours, minutes, seconds) = (time.next()??, time.next()??, time.next()??);
+	
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	let days = era * 146097 + day_of_era - 719468;
+	
+	Some(((days * 24 + hours) * 60 + minutes) * 60_000 + seconds * 1000 + millis)
+}
+
+fn now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |d| d.as_millis() as i64)
+}
+
+/**
+Sorts on the given values, with ties broken by id, and drops everything up to and including
+the tiebreaker item if it is one of the ties.
+*/
+fn sort_and_tiebreak<T>(
+	items: &mut Vec<(f64, &str, T)>,
+	order: SortOrder,
+	tiebreaker: Option<(f64, &str)>,
+) {
+	let compare = |a: &(f64, &str), b: &(f64, &str)| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1));
+	
+	items.sort_by(|a, b| {
+		let ordering = compare(&(a.0, a.1), &(b.0, b.1));
+		if order.is_ascending() { ordering } else { ordering.reverse() }
+	});
+	
+	if let Some((value, id)) = tiebreaker {
+		items.retain(|item| {
+			//Only ties get skipped, the range filters are responsible for the rest
+			if item.0.total_cmp(&value) != Ordering::Equal {
+				return true;
+			}
+			let ordering = compare(&(item.0, item.1), &(value, id));
+			if order.is_ascending() {
+				ordering == Ordering::Greater
+			} else {
+				ordering == Ordering::Less
+			}
+		});
+	}
+}
+
+fn timestamp_value(timestamp: &str) -> f64 {
+	parse_timestamp(timestamp).unwrap_or(0) as f64
+}
+
+fn level_sort_value(level: &Level, property: LevelSortProperty) -> f64 {
+	use LevelSortProperty::*;
+	if property == CreatedAt {
+		return timestamp_value(&level.created_at);
+	}
+	let Some(stats) = &level.stats else {
+		return 0.0;
+	};
+	match property {
+		CreatedAt => unreachable!(),
+		PlayTime => stats.play_time as f64,
+		ReplayValue => stats.replay_value as f64,
+		ExposureBucks => stats.exposure_bucks as f64,
+		#[cfg(feature="undocumented")]
+		HiddenGem => stats.hidden_gem as f64,
+		#[cfg(not(feature="undocumented"))]
+		HiddenGem => 0.0,
+		#[cfg(feature="undocumented")]
+		Attempts => stats.attempts as f64,
+		#[cfg(feature="undocumented")]
+		Favorites => stats.favorites as f64,
+		#[cfg(feature="undocumented")]
+		Likes => stats.likes as f64,
+		#[cfg(feature="undocumented")]
+		Players => stats.players as f64,
+		#[cfg(feature="undocumented")]
+		Successes => stats.successes as f64,
+		#[cfg(feature="undocumented")]
+		ClearRate => stats.clear_rate.get() as f64,
+		#[cfg(feature="undocumented")]
+		FailureRate => stats.failure_rate.get() as f64,
+		#[cfg(feature="undocumented")]
+		TimePerWin => stats.time_per_win.get() as f64,
+		#[cfg(feature="undocumented")]
+		Diamonds => stats.diamonds as f64,
+	}
+}
+
+fn player_sort_value(player: &Player, property: PlayerSortProperty) -> f64 {
+	use PlayerSortProperty::*;
+	let stats = &player.stats;
+	match property {
+		CreatedAt => timestamp_value(&player.created_at),
+		UpdatedAt => timestamp_value(&player.updated_at),
+		Subscribers => stats.subscribers as f64,
+		PlayTime => stats.play_time as f64,
+		Plays => stats.plays as f64,
+		Trophies => stats.time_trophies as f64,
+		Shoes => stats.shoes as f64,
+		Crowns => stats.crowns as f64,
+		Published => stats.published as f64,
+		#[cfg(feature="undocumented")]
+		LevelsPlayed => stats.levels_played as f64,
+		#[cfg(feature="undocumented")]
+		Wins => stats.wins as f64,
+		#[cfg(feature="undocumented")]
+		Fails => stats.fails as f64,
+		#[cfg(feature="undocumented")]
+		NumFollowing => stats.num_following as f64,
+		#[cfg(feature="undocumented")]
+		TowerTrials => stats.tower_trials as f64,
+		#[cfg(feature="undocumented")]
+		TimeTrophies => stats.time_trophies as f64,
+		#[cfg(feature="undocumented")]
+		FaveGen => stats.fave_gen as f64,
+		#[cfg(feature="undocumented")]
+		LikeGen => stats.like_gen as f64,
+		#[cfg(feature="undocumented")]
+		BucksTipped => stats.bucks_tipped as f64,
+		#[cfg(feature="undocumented")]
+		TipsGotten => stats.tips_gotten as f64,
+		#[cfg(feature="undocumented")]
+		CampaignProgress => stats.campaign_progress as f64,
+	}
+}
+
+fn players(dataset: &Dataset, params: &Params) -> Result<Vec<Player>, Failure> {
+	let user_ids = params.list("userIds", PlayerSearch::MAX_USERS)?;
+	let limit = params.limit(PlayerSearch::MAX_LIMIT)?;
+	let sort = params.get::<PlayerSearchSort>("sort")?
+		.unwrap_or_else(|| PlayerSearchSort::descending(PlayerSortProperty::CreatedAt));
+	let include_aliases = params.flag("includeAliases")?;
+	let include_interactions = params.flag("includeMyInteractions")?;
+	
+	let mut found = Vec::new();
+	for player in &dataset.players {
+		if let Some(ids) = &user_ids {
+			if !ids.contains(&player.user_id) {
+				continue;
+			}
+		}
+		if !params.in_range("Subscribers", player.stats.subscribers)?
+			|| !params.in_range("PlayTime", player.stats.play_time)?
+			|| !params.in_time_range("CreatedAt", &player.created_at)?
+			|| !params.in_time_range("UpdatedAt", &player.updated_at)?
+		{
+			continue;
+		}
+		found.push((player_sort_value(player, *sort.property()), player.id.as_str(), player));
+	}
+	
+	let tiebreaker = params.get::<String>("tiebreakerItemId")?;
+	let tiebreaker = tiebreaker.as_ref().and_then(|id| {
+		dataset.players.iter()
+			.find(|p| &p.id == id)
+			.map(|p| (player_sort_value(p, *sort.property()), id.as_str()))
+	});
+	sort_and_tiebreak(&mut found, sort.order(), tiebreaker);
+	
+	let players = found.into_iter()
+		.take(limit)
+		.map(|(_, _, player)| {
+			let mut player = player.clone();
+			if !include_aliases {
+				player.alias = None;
+			}
+			if !include_interactions {
+				player.interactions = None;
+			}
+			player
+		})
+		.collect();
+	Ok(players)
+}
+
+fn levels(dataset: &Dataset, params: &Params) -> Result<Vec<Level>, Failure> {
+	let user_ids = params.list("userIds", LevelSearch::MAX_USERS)?;
+	let level_ids = params.list("levelIds", LevelSearch::MAX_LEVELS)?;
+	let limit = params.limit(LevelSearch::MAX_LIMIT)?;
+	let sort = params.get::<LevelSearchSort>("sort")?
+		.unwrap_or_else(|| LevelSearchSort::descending(LevelSortProperty::HiddenGem));
+	let tag = params.get::<String>("tags")?;
+	let marketing = params.flag("marketing")?;
+	let daily_build = params.flag("dailyBuild")?;
+	let tower = params.flag("tower")? || (user_ids.is_none() && level_ids.is_none());
+	let include_stats = params.flag("includeStats")?;
+	let include_records = params.flag("includeRecords")?;
+	let include_aliases = params.flag("includeAliases")?;
+	let include_interactions = params.flag("includeMyInteractions")?;
+	let diamonds = params.get::<u8>("diamonds")?;
+	let min_seconds_ago = params.get::<i64>("minSecondsAgo")?;
+	let max_seconds_ago = params.get::<i64>("maxSecondsAgo")?;
+	
+	if marketing && daily_build {
+		return Err(Failure::bad_request("marketing and dailyBuild can't be combined"));
+	}
+	#[cfg(not(feature="undocumented"))]
+	if params.has_range("HiddenGem") {
+		return Err(Failure::bad_request("the mock can only filter on HiddenGem with the undocumented feature"));
+	}
+	
+	let now = now();
+	let mut found = Vec::new();
+	for level in &dataset.levels {
+		if let Some(ids) = &user_ids {
+			if !ids.contains(&level.user_id) {
+				continue;
+			}
+		}
+		if let Some(ids) = &level_ids {
+			if !ids.contains(&level.level_id) {
+				continue;
+			}
+		}
+		if let Some(tag) = &tag {
+			if !level.tags.contains(tag) {
+				continue;
+			}
+		}
+		
+		let is_tower = level.tower == Some(true);
+		let is_daily_build = level.daily_build == Some(true);
+		let in_department = if daily_build {
+			is_daily_build
+		} else if marketing {
+			!is_tower && !is_daily_build
+		} else {
+			!tower || is_tower
+		};
+		if !in_department {
+			continue;
+		}
+		
+		if !params.in_time_range("CreatedAt", &level.created_at)? {
+			continue;
+		}
+		let age = parse_timestamp(&level.created_at).map(|created| (now - created) / 1000);
+		if min_seconds_ago.is_some() || max_seconds_ago.is_some() {
+			let Some(age) = age else {
+				continue;
+			};
+			if min_seconds_ago.is_some_and(|min| age < min) || max_seconds_ago.is_some_and(|max| age > max) {
+				continue;
+			}
+		}
+		
+		let stat_filters = ["PlayTime", "ExposureBucks", "ReplayValue", "HiddenGem", "Diamonds"];
+		let filters_stats = diamonds.is_some() || stat_filters.iter().any(|name| params.has_range(name));
+		if filters_stats {
+			let Some(stats) = &level.stats else {
+				continue;
+			};
+			let diamonds_match = match diamonds {
+				Some(diamonds) => stats.diamonds == diamonds,
+				None => params.in_range("Diamonds", stats.diamonds as Stat)?,
+			};
+			#[cfg(feature="undocumented")]
+			let hidden_gem_match = params.in_range("HiddenGem", stats.hidden_gem)?;
+			#[cfg(not(feature="undocumented"))]
+			let hidden_gem_match = true;
+			if !diamonds_match
+				|| !hidden_gem_match
+				|| !params.in_range("PlayTime", stats.play_time)?
+				|| !params.in_range("ExposureBucks", stats.exposure_bucks)?
+				|| !params.in_range("ReplayValue", stats.replay_value)?
+			{
+				continue;
+			}
+		}
+		
+		found.push((level_sort_value(level, *sort.property()), level.id.as_str(), level));
+	}
+	
+	let tiebreaker = params.get::<String>("tiebreakerItemId")?;
+	let tiebreaker = tiebreaker.as_ref().and_then(|id| {
+		dataset.levels.iter()
+			.find(|l| &l.id == id)
+			.map(|l| (level_sort_value(l, *sort.property()), id.as_str()))
+	});
+	sort_and_tiebreak(&mut found, sort.order(), tiebreaker);
+	
+	let levels = found.into_iter()
+		.take(limit)
+		.map(|(_, _, level)| {
+			let mut level = level.clone();
+			if !include_stats {
+				level.stats = None;
+			}
+			if !include_records {
+				level.records = None;
+			}
+			if !include_aliases {
+				level.alias = None;
+				if let Some(records) = &mut level.records {
+					for record in records.high_score.iter_mut().chain(records.fastest_time.iter_mut()) {
+						record.alias = None;
+					}
+				}
+			}
+			if !include_interactions {
+				level.interactions = None;
+			}
+			level
+		})
+		.collect();
+	Ok(levels)
+}
+
+fn aliases(dataset: &Dataset, params: &Params) -> Result<Vec<Alias>, Failure> {
+	let user_ids = params.list("userIds", AliasSearch::MAX_USERS)?
+		.ok_or_else(|| Failure::bad_request("userIds is required"))?;
+	
+	let aliases = user_ids.into_iter()
+		.map(|user_id| {
+			dataset.players.iter()
+				.find(|p| p.user_id == user_id)
+				.and_then(|p| p.alias.clone())
+				//Same as what Rumpus returns for deleted accounts
+				.unwrap_or(Alias {
+					user_id,
+					alias_type: None,
+					alias: None,
+					anonymous: Some(true),
+					extra: Extra::default(),
+				})
+		})
+		.collect();
+	Ok(aliases)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn timestamps() {
+		assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+		assert_eq!(parse_timestamp("1970-01-01T00:00:01.5Z"), Some(1500));
+		assert_eq!(parse_timestamp("2019-02-28T01:02:03.004Z"), Some(1551315723004));
+		assert_eq!(parse_timestamp("2020-03-01T00:00:00.000Z"), Some(1583020800000));
+		assert_eq!(parse_timestamp("2020-03-01"), None);
+	}
+}
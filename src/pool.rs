@@ -0,0 +1,453 @@
+/*!
+
+Spreading requests over several delegation keys.
+
+Rumpus rate limits every delegation key separately, so more keys means more requests.
+A [KeyPool] is a [Transport] that sends every request with the key that has the most of its budget left,
+so it can be used anywhere a single key can:
+
+```no_run
+# async fn example() -> Result<(), Box<dyn std::error::Error>> {
+use std::time::Duration;
+use rumpus_ce::{client::RumpusClient, key::KeySource, pool::KeyPool};
+
+let pool = KeyPool::new()
+	.budget(30, Duration::from_secs(60))?
+	.key(KeySource::file("first-key.txt").load()?, rumpus_ce::API_URL)?
+	.key(KeySource::file("second-key.txt").load()?, rumpus_ce::API_URL)?;
+//Learns which account every key belongs to, and drops the ones that don't work
+pool.validate().await;
+
+let client = RumpusClient::new(pool.clone());
+//Requests that act on behalf of an account should use that account's key
+if let Some(mine) = pool.pinned("your-user-id") {
+	let my_client = RumpusClient::new(mine);
+}
+# Ok(())
+# }
+```
+
+Rumpus doesn't report how much of its budget a key has left, so the pool keeps count itself.
+Every key gets the same [budget](KeyPool::budget) of requests per window.
+When a request does get rate limited (HTTP 429), that key isn't used again until its window is over.
+When a key gets rejected (HTTP 401 or 403), it's considered revoked and isn't used anymore.
+Both are retried with a different key.
+If every key got rate limited, the caller gets the last 429 response,
+and once every key is revoked, requests fail with [PoolError::AllRevoked] instead of the 401 or 403.
+The requests that got retried still show up in the [metrics](crate::metrics) and on the `rumpus_request` span, see the [crate docs](crate).
+When every key has used its budget, requests wait until one of the windows is over.
+
+*/
+
+use std::{
+	sync::{Arc, Mutex, MutexGuard},
+	time::{Duration, Instant},
+};
+use thiserror::Error;
+use super::{
+	client::{ClientError, RumpusClient},
+	key::DelegationKey,
+	transport::{scope_of, RestsonTransport, Transport, TransportError, TransportFuture},
+};
+
+#[derive(Error, Debug)]
+pub enum PoolError {
+	#[error("the key pool has no keys")]
+	Empty,
+	#[error("every key in the pool was revoked")]
+	AllRevoked,
+	#[error("the pinned key was revoked")]
+	PinnedRevoked,
+	#[error("keys need a budget of at least one request")]
+	ZeroBudget,
+}
+
+struct Slot {
+	key: Option<DelegationKey>,
+	transport: Arc<dyn Transport>,
+	///The user id of the account the key belongs to, once known
+	owner: Option<String>,
+	window_start: Instant,
+	used: u32,
+	///Rate limited by Rumpus until then
+	limited_until: Option<Instant>,
+	revoked: bool,
+}
+
+impl Slot {
+	///How many requests this key can still make in its current window
+	fn remaining(&mut self, budget: u32, window: Duration, now: Instant) -> u32 {
+		if self.revoked || self.limited_until.is_some_and(|until| until > now) {
+			return 0;
+		}
+		if now.duration_since(self.window_start) >= window {
+			self.window_start = now;
+			self.used = 0;
+			self.limited_until = None;
+		}
+		budget.saturating_sub(self.used)
+	}
+	
+	///When this key can make requests again
+	fn available_at(&self, window: Duration) -> Option<Instant> {
+		if self.revoked {
+			return None;
+		}
+		let window_end = self.window_start + window;
+		Some(self.limited_until.map_or(window_end, |until| until.max(window_end)))
+	}
+	
+	///Records the budget of this key on the current request span, the transport records the key itself
+	#[cfg(feature="tracing")]
+	fn trace(&self, remaining: u32) {
+		tracing::Span::current().record("remaining", remaining);
+	}
+}
+
+struct Inner {
+	slots: Vec<Slot>,
+	budget: u32,
+	window: Duration,
+	///Where to start looking for the key with the most budget, so ties are spread out
+	next: usize,
+}
+
+enum Pick {
+	Key(usize, Arc<dyn Transport>),
+	Wait(Instant),
+	Revoked,
+}
+
+impl Inner {
+	fn pick(&mut self, now: Instant) -> Pick {
+		let len = self.slots.len();
+		let (budget, window) = (self.budget, self.window);
+		let best = (0..len)
+			.map(|offset| (self.next + offset) % len)
+			.map(|i| (i, self.slots[i].remaining(budget, window, now)))
+			.filter(|&(_, remaining)| remaining > 0)
+			//max_by_key returns the last of equal elements, so reverse to prefer the first
+			.rev()
+			.max_by_key(|&(_, remaining)| remaining);
+		
+		if let Some((i, _remaining)) = best {
+			self.next = (i + 1) % len;
+			let slot = &mut self.slots[i];
+			slot.used += 1;
+			#[cfg(feature="tracing")]
+			slot.trace(_remaining - 1);
+			return Pick::Key(i, slot.transport.clone());
+		}
+		
+		match self.slots.iter().filter_map(|slot| slot.available_at(window)).min() {
+			Some(at) => Pick::Wait(at),
+			None => Pick::Revoked,
+		}
+	}
+	
+	///Like [Self::pick], but only for the key at `i`
+	fn pick_slot(&mut self, i: usize, now: Instant) -> Pick {
+		let (budget, window) = (self.budget, self.window);
+		let slot = &mut self.slots[i];
+		let remaining = slot.remaining(budget, window, now);
+		if remaining > 0 {
+			slot.used += 1;
+			#[cfg(feature="tracing")]
+			slot.trace(remaining - 1);
+			return Pick::Key(i, slot.transport.clone());
+		}
+		match slot.available_at(window) {
+			Some(at) => Pick::Wait(at),
+			None => Pick::Revoked,
+		}
+	}
+	
+	///Updates the state of a key after it got a response. Returns whether the request should be retried with a different key.
+	fn handle(&mut self, i: usize, response: &Result<String, TransportError>, now: Instant) -> bool {
+		let window = self.window;
+		let slot = &mut self.slots[i];
+		match response.as_ref().err().and_then(TransportError::status) {
+			Some(_status @ (401 | 403)) => {
+				slot.revoked = true;
+				#[cfg(feature="tracing")]
+				tracing::warn!(key = slot.key.as_ref().map(tracing::field::display), status = _status, "delegation key revoked");
+				true
+			},
+			Some(429) => {
+				slot.limited_until = Some(now.max(slot.window_start + window));
+				#[cfg(feature="tracing")]
+				tracing::warn!(key = slot.key.as_ref().map(tracing::field::display), status = 429, "delegation key rate limited");
+				true
+			},
+			_ => false,
+		}
+	}
+}
+
+///Where a key in a [KeyPool] is at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStatus {
+	///`None` for keys added as [a transport](KeyPool::transport)
+	pub key: Option<DelegationKey>,
+	///The user id of the account the key belongs to, if it has been [validated](KeyPool::validate)
+	pub owner: Option<String>,
+	///How many requests it can still make in its current window
+	pub remaining: u32,
+	pub revoked: bool,
+}
+
+///A pool of delegation keys, see the [module docs](self)
+#[derive(Clone)]
+pub struct KeyPool {
+	inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for KeyPool {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl std::fmt::Debug for KeyPool {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		f.debug_struct("KeyPool")
+			.field("keys", &self.status())
+			.finish_non_exhaustive()
+	}
+}
+
+impl KeyPool {
+	///Requests per key per window, a guess since Rumpus doesn't document its limits
+	pub const DEFAULT_BUDGET: u32 = 60;
+	pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+	
+	///A pool without keys
+	pub fn new() -> Self {
+		Self {
+			inner: Arc::new(Mutex::new(Inner {
+				slots: Vec::new(),
+				budget: Self::DEFAULT_BUDGET,
+				window: Self::DEFAULT_WINDOW,
+				next: 0,
+			})),
+		}
+	}
+	
+	fn lock(&self) -> MutexGuard<'_, Inner> {
+		//The bookkeeping stays usable even if a thread panicked while holding the lock
+		self.inner.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+	
+	/**
+	How many requests every key may make per `window`.
+	
+	# Errors
+	[PoolError::ZeroBudget] if `requests` is 0, as no key could make any request.
+	*/
+	pub fn budget(self, requests: u32, window: Duration) -> Result<Self, PoolError> {
+		if requests == 0 {
+			return Err(PoolError::ZeroBudget);
+		}
+		{
+			let mut inner = self.lock();
+			inner.budget = requests;
+			inner.window = window;
+		}
+		Ok(self)
+	}
+	
+	/**
+	Adds a key, sending its requests to `url` with a [RestsonTransport].
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If `url` is not a valid URL
+	*/
+	pub fn key(self, key: DelegationKey, url: impl AsRef<str>) -> Result<Self, restson::Error> {
+		let transport = RestsonTransport::with_key_url(key.expose(), url)?;
+		Ok(self.add(Some(key), Arc::new(transport)))
+	}
+	
+	///Adds a transport that sends along its own key
+	pub fn transport(self, transport: impl Transport + 'static) -> Self {
+		self.add(None, Arc::new(transport))
+	}
+	
+	fn add(self, key: Option<DelegationKey>, transport: Arc<dyn Transport>) -> Self {
+		self.lock().slots.push(Slot {
+			key,
+			transport,
+			owner: None,
+			window_start: Instant::now(),
+			used: 0,
+			limited_until: None,
+			revoked: false,
+		});
+		self
+	}
+	
+	///How many keys are in the pool, including revoked ones
+	pub fn len(&self) -> usize {
+		self.lock().slots.len()
+	}
+	
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+	
+	///Where every key is at, in the order they were added
+	pub fn status(&self) -> Vec<KeyStatus> {
+		let mut inner = self.lock();
+		let (budget, window) = (inner.budget, inner.window);
+		let now = Instant::now();
+		inner.slots.iter_mut()
+			.map(|slot| KeyStatus {
+				remaining: slot.remaining(budget, window, now),
+				key: slot.key.clone(),
+				owner: slot.owner.clone(),
+				revoked: slot.revoked,
+			})
+			.collect()
+	}
+	
+	/**
+	Asks Rumpus about every key that hasn't been revoked, to learn which account it belongs to.
+	Keys Rumpus rejects get revoked.
+	
+	Returns the errors of the keys that couldn't be checked for another reason, with their index.
+	*/
+	pub async fn validate(&self) -> Vec<(usize, ClientError)> {
+		let mut errors = Vec::new();
+		for i in 0..self.len() {
+			if self.lock().slots[i].revoked {
+				continue;
+			}
+			let client = RumpusClient::new(PinnedKey { pool: self.clone(), index: i });
+			match client.key_info().await {
+				Ok(info) => self.lock().slots[i].owner = Some(info.user_id),
+				//Already revoked by the pinned key
+				Err(ClientError::Transport(err)) if matches!(err.status(), Some(401 | 403)) => {},
+				Err(err) => errors.push((i, err)),
+			}
+		}
+		errors
+	}
+	
+	/**
+	A transport that always uses the key belonging to the account with `user_id`,
+	for requests that act on behalf of that account.
+	
+	`None` if no key in the pool is known to belong to that account, see [Self::validate].
+	It doesn't fail over to other keys, and waits for the key's budget instead.
+	*/
+	pub fn pinned(&self, user_id: &str) -> Option<PinnedKey> {
+		let index = self.lock().slots.iter().position(|slot| !slot.revoked && slot.owner.as_deref() == Some(user_id))?;
+		Some(PinnedKey {
+			pool: self.clone(),
+			index,
+		})
+	}
+	
+	async fn send(&self, path: &str) -> Result<String, TransportError> {
+		let mut limited = 0;
+		#[cfg(feature="tracing")]
+		let mut retries = 0u32;
+		loop {
+			let pick = self.lock().pick(Instant::now());
+			let (i, transport) = match pick {
+				Pick::Key(i, transport) => (i, transport),
+				Pick::Wait(at) => {
+					#[cfg(feature="tracing")]
+					tracing::debug!(wait_ms = at.saturating_duration_since(Instant::now()).as_millis() as u64, "every key used its budget, waiting");
+					#[cfg(feature="metrics")]
+					crate::metrics::rate_limit_wait("pool", at.saturating_duration_since(Instant::now()));
+					tokio::time::sleep_until(at.into()).await;
+					continue;
+				},
+				Pick::Revoked if self.is_empty() => return Err(TransportError::other(PoolError::Empty)),
+				Pick::Revoked => return Err(TransportError::other(PoolError::AllRevoked)),
+			};
+			
+			#[cfg(feature="metrics")]
+			let start = Instant::now();
+			let response = transport.get(path).await;
+			#[cfg(feature="tracing")]
+			tracing::Span::current().record("status", response.as_ref().map_or_else(TransportError::status, |_| Some(200)));
+			let retry = self.lock().handle(i, &response, Instant::now());
+			if response.as_ref().err().and_then(TransportError::status) == Some(429) {
+				limited += 1;
+				//Every key got rate limited, let the caller decide what to do
+				if limited >= self.len() {
+					return response;
+				}
+			}
+			if !retry {
+				return response;
+			}
+			//The client only sees the response of the last request
+			#[cfg(feature="metrics")]
+			crate::metrics::failover(crate::cache::Endpoint::of(path), start.elapsed());
+			#[cfg(feature="tracing")]
+			{
+				retries += 1;
+				tracing::Span::current().record("retries", retries);
+			}
+		}
+	}
+}
+
+impl Transport for KeyPool {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		Box::pin(self.send(path))
+	}
+	
+	///Any key can answer a request, so the scope covers all of them
+	fn scope(&self) -> Option<String> {
+		let scopes = self.lock().slots.iter()
+			.map(|slot| scope_of(&*slot.transport))
+			.collect::<Vec<_>>();
+		Some(format!("pool[{}]", scopes.join(", ")))
+	}
+}
+
+///A single key of a [KeyPool], see [KeyPool::pinned]
+#[derive(Clone)]
+pub struct PinnedKey {
+	pool: KeyPool,
+	index: usize,
+}
+
+impl PinnedKey {
+	async fn send(&self, path: &str) -> Result<String, TransportError> {
+		loop {
+			let pick = self.pool.lock().pick_slot(self.index, Instant::now());
+			let transport = match pick {
+				Pick::Key(_, transport) => transport,
+				Pick::Wait(at) => {
+					#[cfg(feature="tracing")]
+					tracing::debug!(wait_ms = at.saturating_duration_since(Instant::now()).as_millis() as u64, "pinned key used its budget, waiting");
+					#[cfg(feature="metrics")]
+					crate::metrics::rate_limit_wait("pool", at.saturating_duration_since(Instant::now()));
+					tokio::time::sleep_until(at.into()).await;
+					continue;
+				},
+				Pick::Revoked => return Err(TransportError::other(PoolError::PinnedRevoked)),
+			};
+			
+			let response = transport.get(path).await;
+			self.pool.lock().handle(self.index, &response, Instant::now());
+			return response;
+		}
+	}
+}
+
+impl Transport for PinnedKey {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		Box::pin(self.send(path))
+	}
+	
+	fn scope(&self) -> Option<String> {
+		let transport = self.pool.lock().slots[self.index].transport.clone();
+		Some(scope_of(&*transport))
+	}
+}
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::str::FromStr;
 use thiserror::Error;
 use super::types::Stat;
 
@@ -30,6 +31,10 @@ pub enum PageError {
 	RequiresUndocumented {
 		field: &'static str,
 	},
+	#[error("can't page on {property} because the API has no filter for it")]
+	Unfilterable {
+		property: &'static str,
+	},
 }
 
 impl PageError {
@@ -44,25 +49,76 @@ impl PageError {
 	pub fn undocumented(field: &'static str) -> Self {
 		Self::RequiresUndocumented { field }
 	}
+	
+	pub fn unfilterable(property: &'static str) -> Self {
+		Self::Unfilterable { property }
+	}
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{value:?} is not a known sort property")]
+pub struct SortParseError {
+	pub value: String,
+}
+
+///Direction to sort results in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SortOrder {
+	///From smallest to largest, oldest to newest
+	Ascending,
+	///From largest to smallest, newest to oldest. The API default.
+	#[default]
+	Descending,
+}
+
+impl SortOrder {
+	pub fn is_ascending(&self) -> bool {
+		matches!(self, Self::Ascending)
+	}
+	
+	pub fn reversed(&self) -> Self {
+		match self {
+			Self::Ascending => Self::Descending,
+			Self::Descending => Self::Ascending,
+		}
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Sort<P: fmt::Display> {
-	ascending: bool,
+	order: SortOrder,
 	property: P,
 }
 
 impl<P: fmt::Display> Sort<P> {
-	pub fn new(property: P, ascending: bool) -> Self {
+	pub fn new(property: P, order: SortOrder) -> Self {
 		Self {
 			property,
-			ascending,
+			order,
 		}
 	}
+	
+	pub fn ascending(property: P) -> Self {
+		Self::new(property, SortOrder::Ascending)
+	}
+	
+	pub fn descending(property: P) -> Self {
+		Self::new(property, SortOrder::Descending)
+	}
+	
+	pub fn property(&self) -> &P {
+		&self.property
+	}
+	
+	pub fn order(&self) -> SortOrder {
+		self.order
+	}
 }
+
 impl<P: fmt::Display> fmt::Display for Sort<P> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		if self.ascending {
+		//The API sorts descending by default, a - reverses that
+		if self.order.is_ascending() {
 			write!(f, "-")?;
 		}
 		
@@ -70,6 +126,18 @@ impl<P: fmt::Display> fmt::Display for Sort<P> {
 	}
 }
 
+///Parses the format used by the API, e.g. `-createdAt` sorts ascending on creation date
+impl<P: fmt::Display + FromStr<Err = SortParseError>> FromStr for Sort<P> {
+	type Err = SortParseError;
+	
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.strip_prefix('-') {
+			Some(property) => Ok(Self::ascending(property.parse()?)),
+			None => Ok(Self::descending(s.parse()?)),
+		}
+	}
+}
+
 macro_rules! setter {
 	($field:ident, $type:ty, $queryField:literal, customSetter $(, $_:tt)?) => {};
 	($field:ident, $type:ty, $queryField:literal $(, $_:tt)? ) => {
@@ -91,25 +159,123 @@ pub enum PlayerSortProperty {
 	Shoes,
 	Crowns,
 	Published,
+	#[cfg(feature="undocumented")]
+	LevelsPlayed,
+	#[cfg(feature="undocumented")]
+	Wins,
+	#[cfg(feature="undocumented")]
+	Fails,
+	#[cfg(feature="undocumented")]
+	NumFollowing,
+	///Tower trials completed, irregardless of if the time trophy was acquired
+	#[cfg(feature="undocumented")]
+	TowerTrials,
+	#[cfg(feature="undocumented")]
+	TimeTrophies,
+	#[cfg(feature="undocumented")]
+	FaveGen,
+	#[cfg(feature="undocumented")]
+	LikeGen,
+	#[cfg(feature="undocumented")]
+	BucksTipped,
+	#[cfg(feature="undocumented")]
+	TipsGotten,
+	#[cfg(feature="undocumented")]
+	CampaignProgress,
 }
 
-impl fmt::Display for PlayerSortProperty {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl PlayerSortProperty {
+	///All properties that can be sorted on
+	pub const ALL: &'static [Self] = &[
+		Self::CreatedAt,
+		Self::UpdatedAt,
+		Self::Subscribers,
+		Self::PlayTime,
+		Self::Plays,
+		Self::Trophies,
+		Self::Shoes,
+		Self::Crowns,
+		Self::Published,
+		#[cfg(feature="undocumented")]
+		Self::LevelsPlayed,
+		#[cfg(feature="undocumented")]
+		Self::Wins,
+		#[cfg(feature="undocumented")]
+		Self::Fails,
+		#[cfg(feature="undocumented")]
+		Self::NumFollowing,
+		#[cfg(feature="undocumented")]
+		Self::TowerTrials,
+		#[cfg(feature="undocumented")]
+		Self::TimeTrophies,
+		#[cfg(feature="undocumented")]
+		Self::FaveGen,
+		#[cfg(feature="undocumented")]
+		Self::LikeGen,
+		#[cfg(feature="undocumented")]
+		Self::BucksTipped,
+		#[cfg(feature="undocumented")]
+		Self::TipsGotten,
+		#[cfg(feature="undocumented")]
+		Self::CampaignProgress,
+	];
+	
+	///The name of this property as used by the API
+	pub fn name(&self) -> &'static str {
 		use PlayerSortProperty::*;
 		match self {
-			CreatedAt => write!(f, "createdAt"),
-			UpdatedAt => write!(f, "updatedAt"),
-			Subscribers => write!(f, "Subscribers"),
-			PlayTime => write!(f, "PlayTime"),
-			Plays => write!(f, "Plays"),
-			Trophies => write!(f, "Trophies"),
-			Shoes => write!(f, "Shoes"),
-			Crowns => write!(f, "Crowns"),
-			Published => write!(f, "Published"),
+			CreatedAt => "createdAt",
+			UpdatedAt => "updatedAt",
+			Subscribers => "Subscribers",
+			PlayTime => "PlayTime",
+			Plays => "Plays",
+			Trophies => "Trophies",
+			Shoes => "Shoes",
+			Crowns => "Crowns",
+			Published => "Published",
+			#[cfg(feature="undocumented")]
+			LevelsPlayed => "LevelsPlayed",
+			#[cfg(feature="undocumented")]
+			Wins => "Wins",
+			#[cfg(feature="undocumented")]
+			Fails => "Fails",
+			#[cfg(feature="undocumented")]
+			NumFollowing => "NumFollowing",
+			#[cfg(feature="undocumented")]
+			TowerTrials => "ChalWins",
+			#[cfg(feature="undocumented")]
+			TimeTrophies => "TimeTrophies",
+			#[cfg(feature="undocumented")]
+			FaveGen => "FaveGen",
+			#[cfg(feature="undocumented")]
+			LikeGen => "LikeGen",
+			#[cfg(feature="undocumented")]
+			BucksTipped => "BucksTipped",
+			#[cfg(feature="undocumented")]
+			TipsGotten => "TipsGotten",
+			#[cfg(feature="undocumented")]
+			CampaignProgress => "CampaignProg",
 		}
 	}
 }
 
+impl fmt::Display for PlayerSortProperty {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+impl FromStr for PlayerSortProperty {
+	type Err = SortParseError;
+	
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		Self::ALL.iter()
+			.find(|p| p.name() == s)
+			.copied()
+			.ok_or_else(|| SortParseError { value: s.to_owned() })
+	}
+}
+
 pub type PlayerSearchSort = Sort<PlayerSortProperty>;
 
 //Can't generate the struct with the macro because we want to include docs
@@ -174,14 +340,47 @@ impl PlayerSearch {
 	pub fn new() -> Self {
 		Self::default()
 	}
+	
+	pub fn page_after(mut self, after: &super::types::Player) -> Result<Self,PageError> {
+		let Some(ref sort) = self.sort else {
+			return Err(PageError::sort());
+		};
+		
+		self.tiebreaker_item_id = Some(after.id.clone());
+		
+		//ascending => we start low => we change the lowest/minimum
+		let this = match (sort.property, sort.order.is_ascending()) {
+			(PlayerSortProperty::CreatedAt, true) => self.min_created_at(after.created_at.clone()),
+			(PlayerSortProperty::CreatedAt, false) => self.max_created_at(after.created_at.clone()),
+			(PlayerSortProperty::UpdatedAt, true) => self.min_updated_at(after.updated_at.clone()),
+			(PlayerSortProperty::UpdatedAt, false) => self.max_updated_at(after.updated_at.clone()),
+			(PlayerSortProperty::Subscribers, true) => self.min_subscribers(after.stats.subscribers),
+			(PlayerSortProperty::Subscribers, false) => self.max_subscribers(after.stats.subscribers),
+			(PlayerSortProperty::PlayTime, true) => self.min_play_time(after.stats.play_time),
+			(PlayerSortProperty::PlayTime, false) => self.max_play_time(after.stats.play_time),
+			(property, _) => return Err(PageError::unfilterable(property.name())),
+		};
+		
+		Ok(this)
+	}
 }
 
 impl PlayerSearch {
-	pub fn sort(mut self, property: PlayerSortProperty, ascending: bool) -> Self {
-		self.sort = Some(PlayerSearchSort::new(property, ascending));
+	pub fn sort(mut self, property: PlayerSortProperty, order: SortOrder) -> Self {
+		self.sort = Some(PlayerSearchSort::new(property, order));
 		self
 	}
 	
+	///Sort on creation date, newest first
+	pub fn newest_first(self) -> Self {
+		self.sort(PlayerSortProperty::CreatedAt, SortOrder::Descending)
+	}
+	
+	///Sort on creation date, oldest first
+	pub fn oldest_first(self) -> Self {
+		self.sort(PlayerSortProperty::CreatedAt, SortOrder::Ascending)
+	}
+	
 	///Maximum number of results to return. Returns an error if limit is higher than [Self::MAX_LIMIT]
 	pub fn limit(mut self, limit: u8) -> Result<Self, LimitError> {
 		if limit as usize > Self::MAX_LIMIT {
@@ -253,6 +452,47 @@ impl fmt::Display for PlayerSearch {
 
 
 
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+pub struct AliasSearch {
+	///Up to [Self::MAX_USERS] (subject to change) user ids to look up the aliases of.
+	pub user_ids: Option<Vec<String>>,
+}
+
+impl AliasSearch {
+	pub const MAX_USERS: usize = 64;
+	
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	///Look up the aliases of these user ids. Returns an error if the amount of users is higher than [Self::MAX_USERS]
+	pub fn user_ids<S: Into<String>, V: Into<Vec<S>>>(mut self, user_ids: V) -> Result<Self, LimitError> {
+		let user_ids = user_ids.into().into_iter().map(|s| s.into()).collect::<Vec<_>>();
+		if user_ids.len() > Self::MAX_USERS {
+			Err(LimitError::new(user_ids.len(), Self::MAX_USERS))
+		}else {
+			self.user_ids = Some(user_ids);
+			Ok(self)
+		}
+	}
+}
+
+impl fmt::Display for AliasSearch {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if let Some(v) = &self.user_ids {
+			write!(f, "userIds=")?;
+			for (i, code) in v.iter().enumerate() {
+				write!(f, "{}{}", if i!=0 {","} else {""}, code)?;
+			}
+		}
+		
+		Ok(())
+	}
+}
+
+
+
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LevelSortProperty {
 	CreatedAt,
@@ -262,18 +502,104 @@ pub enum LevelSortProperty {
 	ExposureBucks,
 	///Synonymous with the “Featured” list in-game. Is the default and can be used for Tower levels.
 	HiddenGem,
+	#[cfg(feature="undocumented")]
+	Attempts,
+	#[cfg(feature="undocumented")]
+	Favorites,
+	#[cfg(feature="undocumented")]
+	Likes,
+	#[cfg(feature="undocumented")]
+	Players,
+	#[cfg(feature="undocumented")]
+	Successes,
+	#[cfg(feature="undocumented")]
+	ClearRate,
+	#[cfg(feature="undocumented")]
+	FailureRate,
+	#[cfg(feature="undocumented")]
+	TimePerWin,
+	///Difficulty diamonds
+	#[cfg(feature="undocumented")]
+	Diamonds,
 }
 
-impl fmt::Display for LevelSortProperty {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl LevelSortProperty {
+	///All properties that can be sorted on
+	pub const ALL: &'static [Self] = &[
+		Self::CreatedAt,
+		Self::PlayTime,
+		Self::ReplayValue,
+		Self::ExposureBucks,
+		Self::HiddenGem,
+		#[cfg(feature="undocumented")]
+		Self::Attempts,
+		#[cfg(feature="undocumented")]
+		Self::Favorites,
+		#[cfg(feature="undocumented")]
+		Self::Likes,
+		#[cfg(feature="undocumented")]
+		Self::Players,
+		#[cfg(feature="undocumented")]
+		Self::Successes,
+		#[cfg(feature="undocumented")]
+		Self::ClearRate,
+		#[cfg(feature="undocumented")]
+		Self::FailureRate,
+		#[cfg(feature="undocumented")]
+		Self::TimePerWin,
+		#[cfg(feature="undocumented")]
+		Self::Diamonds,
+	];
+	
+	///The name of this property as used by the API
+	pub fn name(&self) -> &'static str {
 		use LevelSortProperty::*;
 		match self {
-			CreatedAt => write!(f, "createdAt"),
-			PlayTime => write!(f, "PlayTime"),
-			ReplayValue => write!(f, "ReplayValue"),
-			ExposureBucks => write!(f, "ExposureBucks"),
-			HiddenGem => write!(f, "HiddenGem"),
+			CreatedAt => "createdAt",
+			PlayTime => "PlayTime",
+			ReplayValue => "ReplayValue",
+			ExposureBucks => "ExposureBucks",
+			HiddenGem => "HiddenGem",
+			#[cfg(feature="undocumented")]
+			Attempts => "Attempts",
+			#[cfg(feature="undocumented")]
+			Favorites => "Favorites",
+			#[cfg(feature="undocumented")]
+			Likes => "Likes",
+			#[cfg(feature="undocumented")]
+			Players => "Players",
+			#[cfg(feature="undocumented")]
+			Successes => "Successes",
+			#[cfg(feature="undocumented")]
+			ClearRate => "ClearRate",
+			#[cfg(feature="undocumented")]
+			FailureRate => "FailureRate",
+			#[cfg(feature="undocumented")]
+			TimePerWin => "TimePerWin",
+			#[cfg(feature="undocumented")]
+			Diamonds => "Diamonds",
+		}
+	}
+}
+
+impl fmt::Display for LevelSortProperty {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
+}
+
+impl FromStr for LevelSortProperty {
+	type Err = SortParseError;
+	
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		//Synonym documented by the API
+		if s == "QAScore" {
+			return Ok(Self::ExposureBucks);
 		}
+		Self::ALL.iter()
+			.find(|p| p.name() == s)
+			.copied()
+			.ok_or_else(|| SortParseError { value: s.to_owned() })
 	}
 }
 
@@ -423,7 +749,7 @@ impl LevelSearch {
 		self.tiebreaker_item_id = Some(after.id.clone());
 		
 		//ascending => we start low => we change the lowest/minimum
-		let this = match (sort.property, sort.ascending) {
+		let this = match (sort.property, sort.order.is_ascending()) {
 			(LevelSortProperty::CreatedAt, true) => self.min_created_at(after.created_at.clone()),
 			(LevelSortProperty::CreatedAt, false) => self.max_created_at(after.created_at.clone()),
 			(LevelSortProperty::PlayTime, asc) => {
@@ -462,9 +788,21 @@ impl LevelSearch {
 				}
 				#[cfg(not(feature="undocumented"))]
 				{
+					let _ = asc;
 					return Err(PageError::undocumented("hidden_gem"));
 				}
 			},
+			#[cfg(feature="undocumented")]
+			(LevelSortProperty::Diamonds, asc) => {
+				let stats = after.stats.as_ref().ok_or(PageError::stat("diamonds"))?;
+				if asc {
+					self.min_diamonds(stats.diamonds)
+				} else {
+					self.max_diamonds(stats.diamonds)
+				}
+			},
+			#[cfg(feature="undocumented")]
+			(property, _) => return Err(PageError::unfilterable(property.name())),
 		};
 		
 		Ok(this)
@@ -472,11 +810,21 @@ impl LevelSearch {
 }
 
 impl LevelSearch {
-	pub fn sort(mut self, property: LevelSortProperty, ascending: bool) -> Self {
-		self.sort = Some(LevelSearchSort::new(property, ascending));
+	pub fn sort(mut self, property: LevelSortProperty, order: SortOrder) -> Self {
+		self.sort = Some(LevelSearchSort::new(property, order));
 		self
 	}
 	
+	///Sort on creation date, newest first
+	pub fn newest_first(self) -> Self {
+		self.sort(LevelSortProperty::CreatedAt, SortOrder::Descending)
+	}
+	
+	///Sort on creation date, oldest first
+	pub fn oldest_first(self) -> Self {
+		self.sort(LevelSortProperty::CreatedAt, SortOrder::Ascending)
+	}
+	
 	///Maximum number of results to return. Returns an error if limit is higher than [Self::MAX_LIMIT]
 	pub fn limit(mut self, limit: u8) -> Result<Self, LimitError> {
 		if limit as usize > Self::MAX_LIMIT {
@@ -572,6 +920,88 @@ impl fmt::Display for LevelSearch {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::{fixture, types::*};
+	
+	fn player() -> Player {
+		Player {
+			updated_at: "2023-04-01T00:00:00.000Z".into(),
+			stats: PlayerStats {
+				subscribers: 12,
+				..Default::default()
+			},
+			..fixture::player(0)
+		}
+	}
+	
+	fn level(stats: bool) -> Level {
+		Level {
+			stats: stats.then_some(LevelStats {
+				replay_value: 7,
+				diamonds: 3,
+				..Default::default()
+			}),
+			..fixture::level(0)
+		}
+	}
+	
+	#[test]
+	fn player_page_after() -> Result<(), PageError> {
+		let p = player();
+		let q = PlayerSearch::new()
+			.sort(PlayerSortProperty::Subscribers, SortOrder::Descending)
+			.page_after(&p)?;
+		assert_eq!(q.max_subscribers, Some(12));
+		assert_eq!(q.tiebreaker_item_id.as_deref(), Some("player00"));
+		
+		let q = PlayerSearch::new()
+			.sort(PlayerSortProperty::UpdatedAt, SortOrder::Ascending)
+			.page_after(&p)?;
+		assert_eq!(q.min_updated_at.as_deref(), Some("2023-04-01T00:00:00.000Z"));
+		
+		let x = PlayerSearch::new()
+			.sort(PlayerSortProperty::Crowns, SortOrder::Descending)
+			.page_after(&p);
+		assert!(matches!(x, Err(PageError::Unfilterable { property: "Crowns" })));
+		
+		let x = PlayerSearch::new().page_after(&p);
+		assert!(matches!(x, Err(PageError::MissingSort)));
+		
+		Ok(())
+	}
+	
+	#[test]
+	fn level_page_after() -> Result<(), PageError> {
+		let q = LevelSearch::new()
+			.sort(LevelSortProperty::ReplayValue, SortOrder::Ascending)
+			.page_after(&level(true))?;
+		assert_eq!(q.min_replay_value, Some(7));
+		
+		let x = LevelSearch::new()
+			.sort(LevelSortProperty::PlayTime, SortOrder::Descending)
+			.page_after(&level(false));
+		assert!(matches!(x, Err(PageError::MissingStats { field: "play_time" })));
+		
+		Ok(())
+	}
+	
+	#[cfg(feature="undocumented")]
+	#[test]
+	fn level_page_after_undocumented() -> Result<(), PageError> {
+		let q = LevelSearch::new()
+			.sort(LevelSortProperty::Diamonds, SortOrder::Descending)
+			.page_after(&level(true))?;
+		assert_eq!(q.max_diamonds, Some(3));
+		
+		let x = LevelSearch::new()
+			.sort(LevelSortProperty::Likes, SortOrder::Descending)
+			.page_after(&level(true));
+		assert!(matches!(x, Err(PageError::Unfilterable { property: "Likes" })));
+		
+		assert_eq!(format!("{}", LevelSearchSort::ascending(LevelSortProperty::ClearRate)), "-ClearRate");
+		assert_eq!(format!("{}", PlayerSearchSort::descending(PlayerSortProperty::TowerTrials)), "ChalWins");
+		
+		Ok(())
+	}
 	
 	#[test]	
 	fn player_simple_query_string() -> Result<(), LimitError> {
@@ -579,7 +1009,7 @@ mod tests {
 			.user_ids(vec!["test", "someone", "m7n6j8"])?
 			.limit(13)?
 			.include_aliases(false)
-			.sort(PlayerSortProperty::CreatedAt, true);
+			.oldest_first();
 		
 		assert_eq!(format!("{}",q),"userIds=test,someone,m7n6j8&sort=-createdAt&limit=13&includeAliases=false");
 		
@@ -593,13 +1023,30 @@ mod tests {
 			.level_ids(vec!["best","epic"])?
 			.limit(14)?
 			.include_records(true)
-			.sort(LevelSortProperty::PlayTime, false);
+			.sort(LevelSortProperty::PlayTime, SortOrder::Descending);
 
 		assert_eq!(format!("{}",q),"userIds=test,someone,m7n6j8&levelIds=best,epic&sort=PlayTime&limit=14&includeRecords=true");
 
 		Ok(())
 	}
 	
+	#[test]
+	fn sort_from_str() -> Result<(), SortParseError> {
+		let sort: LevelSearchSort = "-createdAt".parse()?;
+		assert_eq!(sort, LevelSearchSort::ascending(LevelSortProperty::CreatedAt));
+		let sort: LevelSearchSort = "QAScore".parse()?;
+		assert_eq!(sort, LevelSearchSort::descending(LevelSortProperty::ExposureBucks));
+		
+		for property in PlayerSortProperty::ALL {
+			let sort = PlayerSearchSort::new(*property, SortOrder::Ascending);
+			assert_eq!(sort.to_string().parse::<PlayerSearchSort>()?, sort);
+		}
+		
+		assert!("createdat".parse::<PlayerSearchSort>().is_err());
+		
+		Ok(())
+	}
+	
 	#[test]
 	fn limits_player_ok() {
 		let x = PlayerSearch::new().user_ids(vec!["test"; PlayerSearch::MAX_USERS]);
@@ -0,0 +1,121 @@
+/*!
+
+Rumpus CE has no endpoint to look up the records of a player: leaderboards are only returned embedded in levels
+(see [LevelSearch::include_records](crate::query::LevelSearch::include_records)).
+
+[PlayerRecords] collects the records a player holds from levels fetched that way.
+[RumpusClient::player_records](crate::client::RumpusClient::player_records) pages through a search (e.g. all tower trials) to collect them,
+or feed it levels fetched some other way.
+
+*/
+
+use super::types::{Level, LevelRecords, Record, RecordType};
+
+///A record a player holds on a level
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HeldRecord {
+	pub level_id: String,
+	pub record_type: RecordType,
+	///Position on the leaderboard, 0 is the world record
+	pub rank: usize,
+	pub record: Record,
+}
+
+impl HeldRecord {
+	pub fn is_world_record(&self) -> bool {
+		self.rank == 0
+	}
+}
+
+///All records held by a single player on the levels seen so far
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerRecords {
+	pub user_id: String,
+	pub records: Vec<HeldRecord>,
+}
+
+impl PlayerRecords {
+	pub fn new(user_id: impl Into<String>) -> Self {
+		Self {
+			user_id: user_id.into(),
+			records: Vec::new(),
+		}
+	}
+	
+	///Adds the records of this player from the leaderboards of a level.
+	///Does nothing if the level was fetched without records.
+	pub fn add_level(&mut self, level: &Level) {
+		if let Some(records) = &level.records {
+			self.add_records(&level.level_id, records);
+		}
+	}
+	
+	///Adds the records of this player from the leaderboards of the level with the given id
+	pub fn add_records(&mut self, level_id: &str, records: &LevelRecords) {
+		let held = records.iter()
+			.filter(|(_, _, record)| record.user_id == self.user_id)
+			.map(|(record_type, rank, record)| HeldRecord {
+				level_id: level_id.to_owned(),
+				record_type,
+				rank,
+				record: record.clone(),
+			});
+		self.records.extend(held);
+	}
+	
+	///Records that are the best on their leaderboard
+	pub fn world_records(&self) -> impl Iterator<Item = &HeldRecord> {
+		self.records.iter().filter(|r| r.is_world_record())
+	}
+	
+	///Records that made it onto a leaderboard without being the best
+	pub fn personal_bests(&self) -> impl Iterator<Item = &HeldRecord> {
+		self.records.iter().filter(|r| !r.is_world_record())
+	}
+	
+	///Records of the given leaderboard type
+	pub fn of_type(&self, record_type: RecordType) -> impl Iterator<Item = &HeldRecord> {
+		self.records.iter().filter(move |r| r.record_type == record_type)
+	}
+}
+
+impl<'l> Extend<&'l Level> for PlayerRecords {
+	fn extend<I: IntoIterator<Item = &'l Level>>(&mut self, levels: I) {
+		for level in levels {
+			self.add_level(level);
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixture::record, types::{Extra, Float}};
+	
+	#[test]
+	fn collects_ranks() {
+		let records = LevelRecords {
+			high_score: vec![record("other", 900.0), record("m7n6j8", 800.0)],
+			fastest_time: vec![record("m7n6j8", 12.5), record("other", 13.0)],
+			extra: Extra::default(),
+		};
+		
+		let mut held = PlayerRecords::new("m7n6j8");
+		held.add_records("abcdefg", &records);
+		
+		assert_eq!(held.records.len(), 2);
+		
+		let wr = held.world_records().collect::<Vec<_>>();
+		assert_eq!(wr.len(), 1);
+		assert_eq!(wr[0].record_type, RecordType::FastestTime);
+		assert_eq!(wr[0].record.value, Float(12.5));
+		
+		let pb = held.personal_bests().collect::<Vec<_>>();
+		assert_eq!(pb.len(), 1);
+		assert_eq!(pb[0].record_type, RecordType::HighScore);
+		assert_eq!(pb[0].rank, 1);
+		assert_eq!(pb[0].level_id, "abcdefg");
+	}
+}
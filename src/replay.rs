@@ -0,0 +1,208 @@
+/*!
+
+Recording API responses to disk and replaying them later, so tests can run offline.
+
+A [ReplayClient] either talks to the API like a normal [RestClient] does,
+additionally saves every response as a JSON fixture, or only reads those fixtures back.
+Fixtures are named after the endpoint and query, so the same request always maps to the same file.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::replay::ReplayError> {
+use rumpus_ce::{replay::ReplayClient, types::*, query::*};
+
+let client = ReplayClient::replay("tests/fixtures");
+let players = client.get::<_, Rumpus<Vec<Player>>>(PlayerSearch::new().newest_first()).await?;
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	fs,
+	io,
+	marker::PhantomData,
+	path::{Path, PathBuf},
+};
+use restson::{RestClient, RestPath};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+use thiserror::Error;
+use super::{
+	hash::fnv1a,
+	unknown::{FieldPolicy, UnknownFields, UnknownFieldsError},
+};
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+	#[error(transparent)]
+	Rest(#[from] restson::Error),
+	#[error("couldn't access fixture {path}: {source}")]
+	Io {
+		path: PathBuf,
+		source: io::Error,
+	},
+	#[error("couldn't parse fixture {path}: {source}")]
+	Parse {
+		path: PathBuf,
+		source: serde_json::Error,
+	},
+	#[error(transparent)]
+	UnknownFields(#[from] UnknownFieldsError),
+}
+
+enum Source {
+	Live(RestClient),
+	Record(RestClient, PathBuf),
+	Replay(PathBuf),
+}
+
+///A client that can record responses to, or replay them from, a directory of fixtures
+pub struct ReplayClient {
+	source: Source,
+	field_policy: FieldPolicy,
+}
+
+impl ReplayClient {
+	///Only talks to the API, nothing gets recorded
+	pub fn live(client: RestClient) -> Self {
+		Self {
+			source: Source::Live(client),
+			field_policy: FieldPolicy::default(),
+		}
+	}
+	
+	///Talks to the API and saves every response in `directory`, overwriting existing fixtures
+	pub fn record(client: RestClient, directory: impl Into<PathBuf>) -> Self {
+		Self {
+			source: Source::Record(client, directory.into()),
+			field_policy: FieldPolicy::default(),
+		}
+	}
+	
+	///Never talks to the API, instead reads responses from the fixtures in `directory`
+	pub fn replay(directory: impl Into<PathBuf>) -> Self {
+		Self {
+			source: Source::Replay(directory.into()),
+			field_policy: FieldPolicy::default(),
+		}
+	}
+	
+	///What to do with fields in responses that this crate doesn't know about
+	pub fn field_policy(mut self, policy: FieldPolicy) -> Self {
+		self.field_policy = policy;
+		self
+	}
+	
+	/**
+	Fetches from the API and/or the fixtures, depending on the mode.
+	
+	# Errors
+	- If the request failed
+	- If a fixture couldn't be read or written
+	- If a fixture couldn't be parsed as `T`
+	- If the [field policy](Self::field_policy) rejects the response
+	*/
+	pub async fn get<U, T>(&self, params: U) -> Result<T, ReplayError>
+	where
+		U: Clone,
+		T: RestPath<U> + DeserializeOwned + UnknownFields,
+	{
+		let response = self.fetch::<U, T>(params).await?;
+		self.field_policy.check(&response)?;
+		Ok(response)
+	}
+	
+	async fn fetch<U, T>(&self, params: U) -> Result<T, ReplayError>
+	where
+		U: Clone,
+		T: RestPath<U> + DeserializeOwned,
+	{
+		match &self.source {
+			Source::Live(client) => Ok(client.get::<_, T>(params).await?.into_inner()),
+			Source::Record(client, directory) => {
+				let path = directory.join(fixture_name(&T::get_path(params.clone())?));
+				let raw = client.get::<_, Raw<T>>(params).await?.into_inner();
+				
+				let json = serde_json::to_string_pretty(&raw.0).expect("JSON values serialize");
+				fs::create_dir_all(directory)
+					.and_then(|_| fs::write(&path, json + "\n"))
+					.map_err(|source| ReplayError::Io { path: path.clone(), source })?;
+				
+				serde_json::from_value(raw.0).map_err(|source| ReplayError::Parse { path, source })
+			},
+			Source::Replay(directory) => {
+				let path = directory.join(fixture_name(&T::get_path(params)?));
+				read_fixture(path)
+			},
+		}
+	}
+}
+
+/**
+Reads a fixture file as `T`.
+
+# Errors
+If the file couldn't be read or parsed.
+*/
+pub fn read_fixture<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, ReplayError> {
+	let path = path.as_ref().to_owned();
+	let json = fs::read_to_string(&path)
+		.map_err(|source| ReplayError::Io { path: path.clone(), source })?;
+	serde_json::from_str(&json).map_err(|source| ReplayError::Parse { path, source })
+}
+
+/**
+The file name of the fixture for the request to an endpoint path (including the query).
+
+Characters that don't belong in file names are replaced by `_`.
+Very long names (e.g. from many user ids) are cut short and get a hash of the full path appended to keep them unique.
+*/
+pub fn fixture_name(path: &str) -> String {
+	const MAX_LENGTH: usize = 150;
+	
+	let name = path.chars()
+		.map(|c| if c.is_ascii_alphanumeric() || "@.-".contains(c) { c } else { '_' })
+		.collect::<String>();
+	
+	if name.len() > MAX_LENGTH {
+		format!("{}_{:016x}.json", &name[..MAX_LENGTH], fnv1a(path))
+	} else {
+		format!("{name}.json")
+	}
+}
+
+///The raw JSON of a response, for the endpoint of `T`
+struct Raw<T>(serde_json::Value, PhantomData<T>);
+
+impl<'de, T> Deserialize<'de> for Raw<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		Ok(Self(serde_json::Value::deserialize(deserializer)?, PhantomData))
+	}
+}
+
+impl<U, T: RestPath<U>> RestPath<U> for Raw<T> {
+	fn get_path(params: U) -> Result<String, restson::Error> {
+		T::get_path(params)
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn names() {
+		assert_eq!(fixture_name("delegation/keys/@this"), "delegation_keys_@this.json");
+		assert_eq!(
+			fixture_name("levelhead/players?sort=-createdAt&limit=16"),
+			"levelhead_players_sort_-createdAt_limit_16.json"
+		);
+		
+		let long = format!("levelhead/players?userIds={}", vec!["abcdef"; 64].join(","));
+		let name = fixture_name(&long);
+		assert_eq!(name.len(), 150 + 1 + 16 + 5);
+		assert_ne!(name, fixture_name(&(long + ",other")));
+	}
+}
@@ -1,6 +1,6 @@
 use restson::{RestClient, Error};
 
-const KEY_HEADER: &str = "Rumpus-Delegation-Key";
+pub(crate) const KEY_HEADER: &str = "Rumpus-Delegation-Key";
 
 /**
 
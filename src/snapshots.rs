@@ -0,0 +1,317 @@
+/*!
+
+Tracking how the stats of levels and players change over time.
+
+[LevelStats] and [PlayerStats] only show the current values.
+A [SnapshotStore] keeps the stats of every entity each time they were fetched,
+and can tell how they changed between two points in time as a [LevelStatsDelta] or [PlayerStatsDelta].
+That's enough to chart growth or to find what's trending:
+
+```
+# use std::time::{Duration, SystemTime};
+use rumpus_ce::{snapshots::LevelSnapshots, types::LevelStats};
+
+let mut snapshots = LevelSnapshots::new();
+let yesterday = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+snapshots.record("abcdefg", yesterday, LevelStats { likes: 10, ..Default::default() });
+snapshots.record("abcdefg", SystemTime::now(), LevelStats { likes: 25, ..Default::default() });
+
+let trending = snapshots.trending(yesterday, |delta| delta.likes);
+assert_eq!(trending[0].0, "abcdefg");
+assert_eq!(trending[0].1.likes, 15);
+```
+
+The store can be (de)serialized to keep it around between runs,
+and can be filled from [crawl archives](crate::crawl) through [Extend].
+
+*/
+
+use std::{
+	collections::HashMap,
+	time::SystemTime,
+};
+use serde::{Deserialize, Serialize};
+use super::{
+	crawl::Archived,
+	types::{Level, LevelStats, Player, PlayerStats},
+};
+
+///Stats that can be compared between two points in time
+pub trait Stats {
+	type Delta;
+	
+	///How the stats changed from `earlier` to `self`
+	fn delta_since(&self, earlier: &Self) -> Self::Delta;
+}
+
+///How the stats of a level changed, as later minus earlier
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all="camelCase")]
+pub struct LevelStatsDelta {
+	pub attempts: i64,
+	pub favorites: i64,
+	pub likes: i64,
+	pub play_time: i64,
+	pub players: i64,
+	pub replay_value: i64,
+	pub successes: i64,
+	pub exposure_bucks: i64,
+	pub diamonds: i16,
+	pub clear_rate: f32,
+	pub time_per_win: f32,
+	pub failure_rate: f32,
+	#[cfg(feature="undocumented")]
+	pub hidden_gem: i64,
+}
+
+impl Stats for LevelStats {
+	type Delta = LevelStatsDelta;
+	
+	fn delta_since(&self, earlier: &Self) -> LevelStatsDelta {
+		LevelStatsDelta {
+			attempts: self.attempts as i64 - earlier.attempts as i64,
+			favorites: self.favorites as i64 - earlier.favorites as i64,
+			likes: self.likes as i64 - earlier.likes as i64,
+			play_time: self.play_time as i64 - earlier.play_time as i64,
+			players: self.players as i64 - earlier.players as i64,
+			replay_value: self.replay_value as i64 - earlier.replay_value as i64,
+			successes: self.successes as i64 - earlier.successes as i64,
+			exposure_bucks: self.exposure_bucks as i64 - earlier.exposure_bucks as i64,
+			diamonds: self.diamonds as i16 - earlier.diamonds as i16,
+			clear_rate: self.clear_rate.get() - earlier.clear_rate.get(),
+			time_per_win: self.time_per_win.get() - earlier.time_per_win.get(),
+			failure_rate: self.failure_rate.get() - earlier.failure_rate.get(),
+			#[cfg(feature="undocumented")]
+			hidden_gem: self.hidden_gem as i64 - earlier.hidden_gem as i64,
+		}
+	}
+}
+
+///How the stats of a player changed, as later minus earlier
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all="camelCase")]
+pub struct PlayerStatsDelta {
+	pub subscribers: i64,
+	pub published: i64,
+	pub plays: i64,
+	pub play_time: i64,
+	pub crowns: i64,
+	pub shoes: i64,
+	pub levels_played: i64,
+	pub wins: i64,
+	pub fails: i64,
+	pub num_following: i64,
+	pub tower_trials: i64,
+	pub time_trophies: i64,
+	pub fave_gen: i64,
+	pub like_gen: i64,
+	pub bucks_tipped: i64,
+	pub tips_gotten: i64,
+	pub campaign_progress: i16,
+}
+
+impl Stats for PlayerStats {
+	type Delta = PlayerStatsDelta;
+	
+	fn delta_since(&self, earlier: &Self) -> PlayerStatsDelta {
+		PlayerStatsDelta {
+			subscribers: self.subscribers as i64 - earlier.subscribers as i64,
+			published: self.published as i64 - earlier.published as i64,
+			plays: self.plays as i64 - earlier.plays as i64,
+			play_time: self.play_time as i64 - earlier.play_time as i64,
+			crowns: self.crowns as i64 - earlier.crowns as i64,
+			shoes: self.shoes as i64 - earlier.shoes as i64,
+			levels_played: self.levels_played as i64 - earlier.levels_played as i64,
+			wins: self.wins as i64 - earlier.wins as i64,
+			fails: self.fails as i64 - earlier.fails as i64,
+			num_following: self.num_following as i64 - earlier.num_following as i64,
+			tower_trials: self.tower_trials as i64 - earlier.tower_trials as i64,
+			time_trophies: self.time_trophies as i64 - earlier.time_trophies as i64,
+			fave_gen: self.fave_gen as i64 - earlier.fave_gen as i64,
+			like_gen: self.like_gen as i64 - earlier.like_gen as i64,
+			bucks_tipped: self.bucks_tipped as i64 - earlier.bucks_tipped as i64,
+			tips_gotten: self.tips_gotten as i64 - earlier.tips_gotten as i64,
+			campaign_progress: self.campaign_progress as i16 - earlier.campaign_progress as i16,
+		}
+	}
+}
+
+///The stats of an entity at some point in time
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all="camelCase")]
+pub struct Snapshot<S> {
+	pub taken_at: SystemTime,
+	pub stats: S,
+}
+
+///Snapshots of the stats of many entities, see the [module docs](self)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotStore<S> {
+	///Snapshots per id, ordered on when they were taken
+	history: HashMap<String, Vec<Snapshot<S>>>,
+}
+
+///Snapshots of level stats, by level id
+pub type LevelSnapshots = SnapshotStore<LevelStats>;
+///Snapshots of player stats, by user id
+pub type PlayerSnapshots = SnapshotStore<PlayerStats>;
+
+impl<S> Default for SnapshotStore<S> {
+	fn default() -> Self {
+		Self {
+			history: HashMap::new(),
+		}
+	}
+}
+
+impl<S: Stats> SnapshotStore<S> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	///Records the stats of an entity at a point in time
+	pub fn record(&mut self, id: impl Into<String>, taken_at: SystemTime, stats: S) {
+		let history = self.history.entry(id.into()).or_default();
+		let index = history.partition_point(|s| s.taken_at <= taken_at);
+		history.insert(index, Snapshot { taken_at, stats });
+	}
+	
+	///The ids of all entities with snapshots
+	pub fn ids(&self) -> impl Iterator<Item = &str> {
+		self.history.keys().map(String::as_str)
+	}
+	
+	///All snapshots of an entity, oldest first
+	pub fn history(&self, id: &str) -> &[Snapshot<S>] {
+		self.history.get(id).map_or(&[], Vec::as_slice)
+	}
+	
+	pub fn latest(&self, id: &str) -> Option<&Snapshot<S>> {
+		self.history(id).last()
+	}
+	
+	///The last snapshot taken at or before `time`
+	pub fn at(&self, id: &str, time: SystemTime) -> Option<&Snapshot<S>> {
+		let history = self.history(id);
+		let index = history.partition_point(|s| s.taken_at <= time);
+		index.checked_sub(1).map(|i| &history[i])
+	}
+	
+	///How the stats of an entity changed between the snapshots at `from` and `to`, see [Self::at]
+	pub fn delta(&self, id: &str, from: SystemTime, to: SystemTime) -> Option<S::Delta> {
+		let earlier = self.at(id, from)?;
+		let later = self.at(id, to)?;
+		Some(later.stats.delta_since(&earlier.stats))
+	}
+	
+	/**
+	How the stats of every entity changed since `since` until their latest snapshot,
+	ordered on the score of the change, highest first.
+	
+	Entities without a snapshot from before `since` are compared from their first snapshot instead,
+	and entities with only a single snapshot are left out.
+	*/
+	pub fn trending<K: Ord>(&self, since: SystemTime, score: impl Fn(&S::Delta) -> K) -> Vec<(&str, S::Delta)> {
+		let mut changes = self.history.iter()
+			.filter_map(|(id, history)| {
+				let earlier = self.at(id, since).or(history.first())?;
+				let later = history.last()?;
+				(earlier.taken_at < later.taken_at).then(|| (id.as_str(), later.stats.delta_since(&earlier.stats)))
+			})
+			.collect::<Vec<_>>();
+		changes.sort_by(|a, b| score(&b.1).cmp(&score(&a.1)).then_with(|| a.0.cmp(b.0)));
+		changes
+	}
+}
+
+impl LevelSnapshots {
+	///Records the stats of levels fetched at `taken_at`. Levels fetched without stats are skipped.
+	pub fn record_levels<'l>(&mut self, levels: impl IntoIterator<Item = &'l Level>, taken_at: SystemTime) {
+		for level in levels {
+			if let Some(stats) = &level.stats {
+				self.record(level.level_id.clone(), taken_at, stats.clone());
+			}
+		}
+	}
+}
+
+impl PlayerSnapshots {
+	///Records the stats of players fetched at `taken_at`
+	pub fn record_players<'p>(&mut self, players: impl IntoIterator<Item = &'p Player>, taken_at: SystemTime) {
+		for player in players {
+			self.record(player.user_id.clone(), taken_at, player.stats.clone());
+		}
+	}
+}
+
+impl Extend<Archived<Level>> for LevelSnapshots {
+	fn extend<I: IntoIterator<Item = Archived<Level>>>(&mut self, archived: I) {
+		for archived in archived {
+			self.record_levels([&archived.item], archived.fetched_at());
+		}
+	}
+}
+
+impl Extend<Archived<Player>> for PlayerSnapshots {
+	fn extend<I: IntoIterator<Item = Archived<Player>>>(&mut self, archived: I) {
+		for archived in archived {
+			self.record_players([&archived.item], archived.fetched_at());
+		}
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+	use super::*;
+	use crate::types::Float;
+	
+	fn player(subscribers: i32, crowns: i32) -> PlayerStats {
+		PlayerStats {
+			subscribers,
+			crowns,
+			..Default::default()
+		}
+	}
+	
+	#[test]
+	fn deltas() {
+		let earlier = LevelStats { likes: 3, clear_rate: Float(0.5), diamonds: 6, ..Default::default() };
+		let later = LevelStats { likes: 10, clear_rate: Float(0.25), diamonds: 4, ..Default::default() };
+		let delta = later.delta_since(&earlier);
+		
+		assert_eq!(delta.likes, 7);
+		assert_eq!(delta.clear_rate, -0.25);
+		assert_eq!(delta.diamonds, -2);
+		assert_eq!(delta.players, 0);
+	}
+	
+	#[test]
+	fn history() {
+		let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+		let hour = Duration::from_secs(60 * 60);
+		
+		let mut snapshots = PlayerSnapshots::new();
+		//Out of order on purpose
+		snapshots.record("a", start + hour * 2, player(30, 1));
+		snapshots.record("a", start, player(10, 0));
+		snapshots.record("a", start + hour, player(12, 5));
+		snapshots.record("b", start, player(50, 0));
+		snapshots.record("b", start + hour * 2, player(55, 2));
+		snapshots.record("c", start + hour, player(1, 0));
+		
+		assert_eq!(snapshots.at("a", start + hour / 2).map(|s| s.stats.subscribers), Some(10));
+		assert!(snapshots.at("a", start - hour).is_none());
+		assert_eq!(snapshots.delta("a", start, start + hour).map(|d| d.crowns), Some(5));
+		
+		let trending = snapshots.trending(start, |d| d.subscribers);
+		let ids = trending.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+		assert_eq!(ids, ["a", "b"]);
+		assert_eq!(trending[0].1.subscribers, 20);
+		
+		let trending = snapshots.trending(start + hour, |d| d.crowns);
+		assert_eq!(trending[0], ("b", PlayerStatsDelta { subscribers: 5, crowns: 2, ..Default::default() }));
+	}
+}
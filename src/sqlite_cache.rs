@@ -0,0 +1,499 @@
+/*!
+
+A persistent cache of the levels, players and aliases fetched from Rumpus CE, stored in SQLite.
+
+Unlike the [response cache](crate::cache), this stores entities rather than responses,
+so it survives restarts and can be queried offline, e.g. to look at everything fetched by an earlier crawl.
+Every entity is stored as it was last received, together with when it was fetched.
+Parts a search didn't include, like the stats of a level or the alias of a player,
+keep what was stored for them earlier, so fetching a level without its stats doesn't lose them.
+The entity then keeps the earlier fetch time too, as that's how old those parts are.
+
+When given to a [client](crate::client::RumpusClient::sqlite_cache),
+everything the client fetches gets stored, and single lookups are served from the cache:
+fresh entities are returned as is, stale ones are returned while a fresh copy gets fetched in the background,
+and expired ones get fetched again before returning.
+SQLite calls block, so the client runs them on tokio's blocking threads.
+Calling the cache directly from async code blocks the calling task.
+
+```no_run
+# fn example() -> Result<(), rumpus_ce::sqlite_cache::SqliteCacheError> {
+use rumpus_ce::sqlite_cache::SqliteCache;
+
+let cache = SqliteCache::open("rumpus.sqlite")?;
+for level in cache.levels_by_creator("m7n6j8")? {
+	println!("{} (fetched {:?} ago)", level.value.title, level.age());
+}
+# Ok(())
+# }
+```
+
+*/
+
+use std::{
+	path::Path,
+	sync::{Arc, Mutex, MutexGuard},
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use rusqlite::{params, CachedStatement, Connection, OptionalExtension, Row};
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use super::types::{Alias, Level, Player};
+
+#[derive(Error, Debug)]
+pub enum SqliteCacheError {
+	#[error(transparent)]
+	Sqlite(#[from] rusqlite::Error),
+	///A stored entity couldn't be (de)serialized, e.g. because it was stored by an incompatible version of this crate
+	#[error(transparent)]
+	Json(#[from] serde_json::Error),
+}
+
+///An entity from the cache, with when it was fetched
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cached<T> {
+	pub value: T,
+	pub fetched_at: SystemTime,
+}
+
+impl<T> Cached<T> {
+	///How long ago this was fetched
+	pub fn age(&self) -> Duration {
+		SystemTime::now().duration_since(self.fetched_at).unwrap_or_default()
+	}
+}
+
+///How usable a cached entity is, see [SqliteCache::freshness]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Freshness {
+	///Can be used as is
+	Fresh,
+	///Can be used, but should be fetched again
+	Stale,
+	///Has to be fetched again
+	Expired,
+}
+
+const SCHEMA: &str = "
+	CREATE TABLE IF NOT EXISTS levels (
+		level_id TEXT PRIMARY KEY NOT NULL,
+		user_id TEXT NOT NULL,
+		title TEXT NOT NULL,
+		created_at TEXT NOT NULL,
+		json TEXT NOT NULL,
+		fetched_at INTEGER NOT NULL
+	);
+	CREATE INDEX IF NOT EXISTS levels_user_id ON levels (user_id);
+	CREATE TABLE IF NOT EXISTS players (
+		user_id TEXT PRIMARY KEY NOT NULL,
+		created_at TEXT NOT NULL,
+		json TEXT NOT NULL,
+		fetched_at INTEGER NOT NULL
+	);
+	CREATE TABLE IF NOT EXISTS aliases (
+		user_id TEXT PRIMARY KEY NOT NULL,
+		alias TEXT,
+		json TEXT NOT NULL,
+		fetched_at INTEGER NOT NULL
+	);
+";
+
+///A SQLite database of fetched entities, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct SqliteCache {
+	connection: Arc<Mutex<Connection>>,
+	fresh_for: Duration,
+	stale_for: Duration,
+}
+
+impl SqliteCache {
+	pub const DEFAULT_FRESH_FOR: Duration = Duration::from_secs(5 * 60);
+	pub const DEFAULT_STALE_FOR: Duration = Duration::from_secs(24 * 60 * 60);
+	
+	/**
+	Opens or creates the database at `path`.
+	
+	# Errors
+	If the database couldn't be opened or set up.
+	*/
+	pub fn open(path: impl AsRef<Path>) -> Result<Self, SqliteCacheError> {
+		Self::with_connection(Connection::open(path)?)
+	}
+	
+	/**
+	A database that only lives in memory, mostly useful for tests.
+	
+	# Errors
+	If the database couldn't be set up.
+	*/
+	pub fn open_in_memory() -> Result<Self, SqliteCacheError> {
+		Self::with_connection(Connection::open_in_memory()?)
+	}
+	
+	fn with_connection(connection: Connection) -> Result<Self, SqliteCacheError> {
+		connection.execute_batch(SCHEMA)?;
+		Ok(Self {
+			connection: Arc::new(Mutex::new(connection)),
+			fresh_for: Self::DEFAULT_FRESH_FOR,
+			stale_for: Self::DEFAULT_STALE_FOR,
+		})
+	}
+	
+	///How long after being fetched entities are [fresh](Freshness::Fresh)
+	pub fn fresh_for(mut self, fresh_for: Duration) -> Self {
+		self.fresh_for = fresh_for;
+		self
+	}
+	
+	///How long after being fresh entities are still usable while they get fetched again
+	pub fn stale_for(mut self, stale_for: Duration) -> Self {
+		self.stale_for = stale_for;
+		self
+	}
+	
+	///How usable an entity fetched at `fetched_at` is
+	pub fn freshness(&self, fetched_at: SystemTime) -> Freshness {
+		let age = SystemTime::now().duration_since(fetched_at).unwrap_or_default();
+		if age < self.fresh_for {
+			Freshness::Fresh
+		} else if age < self.fresh_for + self.stale_for {
+			Freshness::Stale
+		} else {
+			Freshness::Expired
+		}
+	}
+	
+	fn lock(&self) -> MutexGuard<'_, Connection> {
+		//SQLite keeps the database consistent itself
+		self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+	
+	/**
+	Stores levels as fetched at `fetched_at`, replacing earlier versions.
+	Stats, records, aliases and interactions the levels don't include are kept from earlier versions,
+	together with when those were fetched.
+	
+	# Errors
+	If the database couldn't be written to.
+	*/
+	pub fn store_levels<'l>(&self, levels: impl IntoIterator<Item = &'l Level>, fetched_at: SystemTime) -> Result<(), SqliteCacheError> {
+		let mut connection = self.lock();
+		let transaction = connection.transaction()?;
+		{
+			let mut statement = transaction.prepare_cached(
+				"INSERT OR REPLACE INTO levels (level_id, user_id, title, created_at, json, fetched_at) VALUES (?, ?, ?, ?, ?, ?)"
+			)?;
+			let mut stored = transaction.prepare_cached("SELECT json, fetched_at FROM levels WHERE level_id = ?")?;
+			for level in levels {
+				let (json, fetched_at) = merge(level, &mut stored, &level.level_id, to_millis(fetched_at))?;
+				statement.execute(params![
+					level.level_id,
+					level.user_id,
+					level.title,
+					level.created_at,
+					json,
+					fetched_at,
+				])?;
+			}
+		}
+		transaction.commit()?;
+		Ok(())
+	}
+	
+	/**
+	Stores players as fetched at `fetched_at`, replacing earlier versions.
+	Aliases and interactions the players don't include are kept from earlier versions,
+	together with when those were fetched.
+	
+	# Errors
+	If the database couldn't be written to.
+	*/
+	pub fn store_players<'p>(&self, players: impl IntoIterator<Item = &'p Player>, fetched_at: SystemTime) -> Result<(), SqliteCacheError> {
+		let mut connection = self.lock();
+		let transaction = connection.transaction()?;
+		{
+			let mut statement = transaction.prepare_cached(
+				"INSERT OR REPLACE INTO players (user_id, created_at, json, fetched_at) VALUES (?, ?, ?, ?)"
+			)?;
+			let mut stored = transaction.prepare_cached("SELECT json, fetched_at FROM players WHERE user_id = ?")?;
+			for player in players {
+				let (json, fetched_at) = merge(player, &mut stored, &player.user_id, to_millis(fetched_at))?;
+				statement.execute(params![
+					player.user_id,
+					player.created_at,
+					json,
+					fetched_at,
+				])?;
+			}
+		}
+		transaction.commit()?;
+		Ok(())
+	}
+	
+	/**
+	Stores aliases as fetched at `fetched_at`, replacing earlier versions.
+	
+	# Errors
+	If the database couldn't be written to.
+	*/
+	pub fn store_aliases<'a>(&self, aliases: impl IntoIterator<Item = &'a Alias>, fetched_at: SystemTime) -> Result<(), SqliteCacheError> {
+		let mut connection = self.lock();
+		let transaction = connection.transaction()?;
+		{
+			let mut statement = transaction.prepare_cached(
+				"INSERT OR REPLACE INTO aliases (user_id, alias, json, fetched_at) VALUES (?, ?, ?, ?)"
+			)?;
+			for alias in aliases {
+				statement.execute(params![
+					alias.user_id,
+					alias.alias,
+					serde_json::to_string(alias)?,
+					to_millis(fetched_at),
+				])?;
+			}
+		}
+		transaction.commit()?;
+		Ok(())
+	}
+	
+	fn query<T: DeserializeOwned>(&self, sql: &str, params: impl rusqlite::Params) -> Result<Vec<Cached<T>>, SqliteCacheError> {
+		let connection = self.lock();
+		let mut statement = connection.prepare_cached(sql)?;
+		let rows = statement.query_map(params, raw_row)?
+			.collect::<Result<Vec<_>, _>>()?;
+		rows.into_iter().map(|(json, fetched_at)| parse(&json, fetched_at)).collect()
+	}
+	
+	fn query_one<T: DeserializeOwned>(&self, sql: &str, params: impl rusqlite::Params) -> Result<Option<Cached<T>>, SqliteCacheError> {
+		let connection = self.lock();
+		let row = connection.prepare_cached(sql)?
+			.query_row(params, raw_row)
+			.optional()?;
+		row.map(|(json, fetched_at)| parse(&json, fetched_at)).transpose()
+	}
+	
+	///The stored level with this id
+	pub fn level(&self, level_id: &str) -> Result<Option<Cached<Level>>, SqliteCacheError> {
+		self.query_one("SELECT json, fetched_at FROM levels WHERE level_id = ?", [level_id])
+	}
+	
+	///The stored player with this user id
+	pub fn player(&self, user_id: &str) -> Result<Option<Cached<Player>>, SqliteCacheError> {
+		self.query_one("SELECT json, fetched_at FROM players WHERE user_id = ?", [user_id])
+	}
+	
+	///The stored alias of this user id
+	pub fn alias(&self, user_id: &str) -> Result<Option<Cached<Alias>>, SqliteCacheError> {
+		self.query_one("SELECT json, fetched_at FROM aliases WHERE user_id = ?", [user_id])
+	}
+	
+	///All stored levels, newest first
+	pub fn levels(&self) -> Result<Vec<Cached<Level>>, SqliteCacheError> {
+		self.query("SELECT json, fetched_at FROM levels ORDER BY created_at DESC", [])
+	}
+	
+	///All stored players, newest first
+	pub fn players(&self) -> Result<Vec<Cached<Player>>, SqliteCacheError> {
+		self.query("SELECT json, fetched_at FROM players ORDER BY created_at DESC", [])
+	}
+	
+	///All stored aliases
+	pub fn aliases(&self) -> Result<Vec<Cached<Alias>>, SqliteCacheError> {
+		self.query("SELECT json, fetched_at FROM aliases ORDER BY user_id", [])
+	}
+	
+	///The stored levels created by a user, newest first
+	pub fn levels_by_creator(&self, user_id: &str) -> Result<Vec<Cached<Level>>, SqliteCacheError> {
+		self.query("SELECT json, fetched_at FROM levels WHERE user_id = ? ORDER BY created_at DESC", [user_id])
+	}
+	
+	///The stored levels whose title contains `text`, ignoring ASCII case, newest first
+	pub fn levels_titled(&self, text: &str) -> Result<Vec<Cached<Level>>, SqliteCacheError> {
+		self.query(
+			"SELECT json, fetched_at FROM levels WHERE instr(lower(title), lower(?)) > 0 ORDER BY created_at DESC",
+			[text],
+		)
+	}
+	
+	/**
+	Removes everything fetched before `time`, returning how many entities were removed.
+	
+	# Errors
+	If the database couldn't be written to.
+	*/
+	pub fn remove_fetched_before(&self, time: SystemTime) -> Result<usize, SqliteCacheError> {
+		let connection = self.lock();
+		let millis = to_millis(time);
+		let mut removed = 0;
+		for table in ["levels", "players", "aliases"] {
+			removed += connection.execute(&format!("DELETE FROM {table} WHERE fetched_at < ?"), [millis])?;
+		}
+		Ok(removed)
+	}
+}
+
+///Entities that can be fetched without some of their parts
+trait Merge: Clone + Serialize + DeserializeOwned {
+	///Whether this is missing parts an earlier version might have
+	fn is_partial(&self) -> bool;
+	///Fills in the missing parts from an earlier version, returns whether it had any of them
+	fn fill_from(&mut self, earlier: Self) -> bool;
+}
+
+impl Merge for Level {
+	fn is_partial(&self) -> bool {
+		self.stats.is_none() || self.records.is_none() || self.alias.is_none() || self.interactions.is_none()
+	}
+	
+	fn fill_from(&mut self, earlier: Self) -> bool {
+		fill(&mut self.stats, earlier.stats)
+			| fill(&mut self.records, earlier.records)
+			| fill(&mut self.alias, earlier.alias)
+			| fill(&mut self.interactions, earlier.interactions)
+	}
+}
+
+impl Merge for Player {
+	fn is_partial(&self) -> bool {
+		self.alias.is_none() || self.interactions.is_none()
+	}
+	
+	fn fill_from(&mut self, earlier: Self) -> bool {
+		fill(&mut self.alias, earlier.alias) | fill(&mut self.interactions, earlier.interactions)
+	}
+}
+
+fn fill<T>(part: &mut Option<T>, earlier: Option<T>) -> bool {
+	let filled = part.is_none() && earlier.is_some();
+	if filled {
+		*part = earlier;
+	}
+	filled
+}
+
+/**
+The JSON and fetch time to store for `entity`, filled in from the version `stored` finds for `key`.
+
+Parts filled in from the earlier version are as old as that version, so it keeps its fetch time then.
+*/
+fn merge<T: Merge>(entity: &T, stored: &mut CachedStatement, key: &str, fetched_at: i64) -> Result<(String, i64), SqliteCacheError> {
+	if !entity.is_partial() {
+		return Ok((serde_json::to_string(entity)?, fetched_at));
+	}
+	let earlier = stored.query_row([key], raw_row).optional()?
+		//An unreadable earlier version has nothing worth keeping
+		.and_then(|(json, earlier_at)| Some((serde_json::from_str(&json).ok()?, earlier_at)));
+	let Some((earlier, earlier_at)) = earlier else {
+		return Ok((serde_json::to_string(entity)?, fetched_at));
+	};
+	let mut entity = entity.clone();
+	let fetched_at = if entity.fill_from(earlier) {
+		fetched_at.min(earlier_at)
+	} else {
+		fetched_at
+	};
+	Ok((serde_json::to_string(&entity)?, fetched_at))
+}
+
+fn raw_row(row: &Row) -> rusqlite::Result<(String, i64)> {
+	Ok((row.get(0)?, row.get(1)?))
+}
+
+fn parse<T: DeserializeOwned>(json: &str, fetched_at: i64) -> Result<Cached<T>, SqliteCacheError> {
+	Ok(Cached {
+		value: serde_json::from_str(json)?,
+		fetched_at: from_millis(fetched_at),
+	})
+}
+
+fn to_millis(time: SystemTime) -> i64 {
+	time.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64
+}
+
+fn from_millis(millis: i64) -> SystemTime {
+	UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixture, types::{Extra, AliasType, LevelInteractions, LevelRecords}};
+	
+	fn alias(user_id: &str, name: &str) -> Alias {
+		Alias {
+			user_id: user_id.into(),
+			alias_type: Some(AliasType::Levelhead),
+			alias: Some(name.into()),
+			anonymous: None,
+			extra: Extra::default(),
+		}
+	}
+	
+	#[test]
+	fn stores() -> Result<(), SqliteCacheError> {
+		let cache = SqliteCache::open_in_memory()?;
+		let then = SystemTime::now() - Duration::from_secs(60 * 60);
+		
+		cache.store_aliases([&alias("a", "First"), &alias("b", "Second")], then)?;
+		cache.store_aliases([&alias("a", "Renamed")], SystemTime::now())?;
+		
+		let a = cache.alias("a")?.expect("alias should be stored");
+		assert_eq!(a.value.alias.as_deref(), Some("Renamed"));
+		assert_eq!(cache.freshness(a.fetched_at), Freshness::Fresh);
+		
+		let b = cache.alias("b")?.expect("alias should be stored");
+		assert_eq!(cache.freshness(b.fetched_at), Freshness::Stale);
+		assert_eq!(cache.clone().stale_for(Duration::ZERO).freshness(b.fetched_at), Freshness::Expired);
+		
+		assert_eq!(cache.remove_fetched_before(SystemTime::now() - Duration::from_secs(60))?, 1);
+		assert_eq!(cache.aliases()?.len(), 1);
+		assert!(cache.alias("c")?.is_none());
+		
+		Ok(())
+	}
+	
+	#[test]
+	fn keeps_parts() -> Result<(), SqliteCacheError> {
+		let cache = SqliteCache::open_in_memory()?;
+		let then = SystemTime::now() - Duration::from_secs(60 * 60);
+		
+		let full = Level {
+			alias: Some(fixture::alias("user00", 0)),
+			..fixture::level(1)
+		};
+		cache.store_levels([&full], then)?;
+		let renamed = Level {
+			title: "Renamed".into(),
+			stats: None,
+			..fixture::level(1)
+		};
+		cache.store_levels([&renamed], SystemTime::now())?;
+		
+		let level = cache.level("code01")?.expect("level should be stored");
+		assert_eq!(level.value.title, "Renamed");
+		assert_eq!(level.value.stats, full.stats);
+		assert_eq!(level.value.alias, full.alias);
+		//The stats are still as old as the first fetch
+		assert_eq!(level.fetched_at, from_millis(to_millis(then)));
+		assert_eq!(cache.freshness(level.fetched_at), Freshness::Stale);
+		
+		//Fetching everything again makes it fresh
+		let everything = Level {
+			records: Some(LevelRecords::default()),
+			interactions: Some(LevelInteractions::default()),
+			..full.clone()
+		};
+		cache.store_levels([&everything], SystemTime::now())?;
+		let level = cache.level("code01")?.expect("level should be stored");
+		assert_eq!(cache.freshness(level.fetched_at), Freshness::Fresh);
+		
+		let player = fixture::player(2);
+		cache.store_players([&player], then)?;
+		cache.store_players([&Player { alias: None, ..player.clone() }], then)?;
+		assert_eq!(cache.player("user02")?.map(|p| p.value.alias), Some(player.alias));
+		
+		Ok(())
+	}
+}
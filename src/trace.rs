@@ -0,0 +1,117 @@
+/*!
+
+Instrumenting requests, for the `tracing` and `metrics` features.
+
+Without them, [RequestSpan] does nothing.
+
+*/
+
+#[cfg(any(feature="tracing", feature="metrics"))]
+use std::time::Instant;
+#[cfg(feature="metrics")]
+use super::cache::Endpoint;
+use super::{
+	client::ClientError,
+	transport::{Transport, TransportError},
+};
+
+///A single request: its span, see the [crate docs](crate) for its fields, and its [metrics](crate::metrics)
+pub(crate) struct RequestSpan {
+	#[cfg(feature="tracing")]
+	span: tracing::Span,
+	#[cfg(feature="metrics")]
+	endpoint: Endpoint,
+}
+
+impl RequestSpan {
+	pub fn new(path: &str) -> Self {
+		#[cfg(not(any(feature="tracing", feature="metrics")))]
+		let _ = path;
+		Self {
+			#[cfg(feature="tracing")]
+			span: {
+				let (endpoint, query) = path.split_once('?').unwrap_or((path, ""));
+				tracing::debug_span!(
+					"rumpus_request",
+					endpoint,
+					query,
+					cache = tracing::field::Empty,
+					status = tracing::field::Empty,
+					latency_ms = tracing::field::Empty,
+					key = tracing::field::Empty,
+					remaining = tracing::field::Empty,
+					retries = tracing::field::Empty,
+					retry = tracing::field::Empty,
+				)
+			},
+			#[cfg(feature="metrics")]
+			endpoint: Endpoint::of(path),
+		}
+	}
+	
+	///Which retry of the [crawler](crate::crawl) this request is, if it is one
+	pub fn retry(&self, _retry: u32) {
+		#[cfg(feature="tracing")]
+		if _retry > 0 {
+			self.span.record("retry", _retry);
+		}
+	}
+	
+	///Whether the response came from the [ResponseCache](crate::cache::ResponseCache), if the client has one
+	pub fn cache(&self, cache: Option<bool>) {
+		let Some(_hit) = cache else {
+			return;
+		};
+		#[cfg(feature="metrics")]
+		super::metrics::cache(self.endpoint, _hit);
+		#[cfg(feature="tracing")]
+		{
+			self.span.record("cache", if _hit { "hit" } else { "miss" });
+			if _hit {
+				tracing::debug!(parent: &self.span, "served from the cache");
+			}
+		}
+	}
+	
+	///Sends the request inside this span, and records how it went
+	pub async fn send(&self, transport: &dyn Transport, path: &str) -> Result<String, TransportError> {
+		#[cfg(any(feature="tracing", feature="metrics"))]
+		let start = Instant::now();
+		#[cfg(feature="tracing")]
+		let result = {
+			use tracing::Instrument;
+			transport.get(path).instrument(self.span.clone()).await
+		};
+		#[cfg(not(feature="tracing"))]
+		let result = transport.get(path).await;
+		
+		#[cfg(feature="metrics")]
+		super::metrics::request(self.endpoint, start.elapsed(), &result);
+		#[cfg(feature="tracing")]
+		{
+			self.span.record("latency_ms", start.elapsed().as_millis() as u64);
+			match &result {
+				//Transports only hand out the body of successful responses
+				Ok(_) => {
+					self.span.record("status", 200);
+					tracing::debug!(parent: &self.span, "request succeeded");
+				},
+				Err(err) => {
+					if let Some(status) = err.status() {
+						self.span.record("status", status);
+					}
+					tracing::warn!(parent: &self.span, error = %err, "request failed");
+				},
+			}
+		}
+		result
+	}
+	
+	///Records that the response couldn't be used
+	pub fn error(&self, _error: &ClientError) {
+		#[cfg(feature="metrics")]
+		super::metrics::error(self.endpoint, _error);
+		#[cfg(feature="tracing")]
+		tracing::warn!(parent: &self.span, error = %_error, "response couldn't be used");
+	}
+}
@@ -0,0 +1,210 @@
+/*!
+
+The HTTP layer the [client](crate::client) sends its requests through.
+
+A [Transport] knows where the API lives and which delegation key to use,
+and turns an endpoint path into the body of the response.
+[RestsonTransport] is the default implementation, but anything implementing the trait can be used instead,
+e.g. a different HTTP stack, a test double, or middleware wrapping another transport:
+
+```
+use rumpus_ce::transport::{Transport, TransportFuture};
+
+struct Logging<T>(T);
+
+impl<T: Transport> Transport for Logging<T> {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		println!("GET {path}");
+		self.0.get(path)
+	}
+}
+```
+
+*/
+
+use std::{
+	error::Error as StdError,
+	future::Future,
+	pin::Pin,
+	sync::Arc,
+};
+use restson::{RestClient, RestPath};
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum TransportError {
+	///The server responded, but not with a success status
+	#[error("server returned HTTP {status}: {body}")]
+	Status {
+		status: u16,
+		body: String,
+	},
+	///The request couldn't be made or the response couldn't be received
+	#[error(transparent)]
+	Other(Box<dyn StdError + Send + Sync>),
+}
+
+impl TransportError {
+	///Wraps any other error a transport runs into
+	pub fn other(error: impl StdError + Send + Sync + 'static) -> Self {
+		Self::Other(Box::new(error))
+	}
+	
+	///The HTTP status the server responded with, if it responded
+	pub fn status(&self) -> Option<u16> {
+		match self {
+			Self::Status { status, .. } => Some(*status),
+			Self::Other(_) => None,
+		}
+	}
+}
+
+///What [Transport::get] returns
+pub type TransportFuture<'a> = Pin<Box<dyn Future<Output = Result<String, TransportError>> + Send + 'a>>;
+
+///Something that can send GET requests to Rumpus CE
+pub trait Transport: Send + Sync {
+	/**
+	Fetches an endpoint and returns the body of the response.
+	
+	`path` is relative to the API url and includes the query, e.g. `levelhead/levels?limit=10`.
+	The transport is responsible for the base url and for sending the delegation key along.
+	
+	# Errors
+	With [TransportError::Status] if the server didn't respond with a success status.
+	*/
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a>;
+	
+	/**
+	Where requests go and who sends them, e.g. the API url and the [fingerprint](crate::key::DelegationKey::fingerprint) of the key.
+	
+	A [response cache](crate::cache) only serves a response to transports with the same scope as the one that received it,
+	so clients with different keys or environments can share a cache.
+	`None` if the transport doesn't know, in which case its responses are only served to the client that got them.
+	*/
+	fn scope(&self) -> Option<String> {
+		None
+	}
+}
+
+///The [scope](Transport::scope) of a transport, or one only it has if it doesn't know its scope
+pub(crate) fn scope_of(transport: &dyn Transport) -> String {
+	transport.scope().unwrap_or_else(|| format!("{:p}", transport as *const dyn Transport as *const ()))
+}
+
+impl<T: Transport + ?Sized> Transport for &T {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		(**self).get(path)
+	}
+	
+	fn scope(&self) -> Option<String> {
+		(**self).scope()
+	}
+}
+
+impl<T: Transport + ?Sized> Transport for Box<T> {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		(**self).get(path)
+	}
+	
+	fn scope(&self) -> Option<String> {
+		(**self).scope()
+	}
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		(**self).get(path)
+	}
+	
+	fn scope(&self) -> Option<String> {
+		(**self).scope()
+	}
+}
+
+///A [Transport] using a restson [RestClient], and therefore hyper and tokio
+pub struct RestsonTransport {
+	client: RestClient,
+	///Only known when the transport set up the client itself
+	scope: Option<String>,
+	///Only known when the transport set up the client itself, and only kept to record it on spans
+	#[cfg(feature="tracing")]
+	key: Option<super::key::DelegationKey>,
+}
+
+impl RestsonTransport {
+	///Uses an already set up client, which should have the delegation key header set
+	pub fn new(mut client: RestClient) -> Self {
+		//Restson only hands out parsed bodies, so we turn the body into a JSON string to get it back unparsed
+		client.set_body_wash_fn(|body| serde_json::Value::String(body).to_string());
+		Self {
+			client,
+			scope: None,
+			#[cfg(feature="tracing")]
+			key: None,
+		}
+	}
+	
+	/**
+	Creates a transport with a given delegation key and url/endpoint.
+	
+	# Errors
+	- If `key` is not a valid HTTP header
+	- If `url` is not a valid URL
+	*/
+	pub fn with_key_url(key: impl AsRef<str>, url: impl AsRef<str>) -> Result<Self, restson::Error> {
+		let client = super::rest_client::try_with_key_url(&key, url.as_ref())?;
+		let delegation_key = super::key::DelegationKey::new(key.as_ref());
+		let this = Self {
+			scope: Some(format!("{} {}", url.as_ref().trim_end_matches('/'), delegation_key.fingerprint())),
+			..Self::new(client)
+		};
+		#[cfg(feature="tracing")]
+		let this = Self {
+			key: Some(delegation_key),
+			..this
+		};
+		Ok(this)
+	}
+	
+	/**
+	Creates a transport with a given delegation key and using [the default API url/endpoint](super::API_URL).
+	
+	# Errors
+	If `key` is not a valid HTTP header.
+	*/
+	pub fn with_key(key: impl AsRef<str>) -> Result<Self, restson::Error> {
+		Self::with_key_url(key, super::API_URL)
+	}
+}
+
+impl Transport for RestsonTransport {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		Box::pin(async move {
+			#[cfg(feature="tracing")]
+			if let Some(key) = &self.key {
+				tracing::Span::current().record("key", tracing::field::display(key));
+			}
+			match self.client.get::<_, Body>(path).await {
+				Ok(response) => Ok(response.into_inner().0),
+				Err(restson::Error::HttpError(status, body)) => Err(TransportError::Status { status, body }),
+				Err(err) => Err(TransportError::other(err)),
+			}
+		})
+	}
+	
+	fn scope(&self) -> Option<String> {
+		self.scope.clone()
+	}
+}
+
+///The unparsed body of a response
+#[derive(Deserialize)]
+struct Body(String);
+
+impl RestPath<&str> for Body {
+	fn get_path(path: &str) -> Result<String, restson::Error> {
+		Ok(path.to_owned())
+	}
+}
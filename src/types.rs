@@ -1,27 +1,98 @@
+use std::{
+	cmp::Ordering,
+	collections::HashSet,
+	fmt,
+	hash::{Hash, Hasher},
+	ops::{Deref, DerefMut},
+};
 use serde::{Serialize, Deserialize};
 
+///The raw values of fields this crate doesn't know about
+pub type ExtraMap = serde_json::Map<String, serde_json::Value>;
+
 ///Wrapper for the generic response data all endpoints return
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="camelCase")]
 pub struct Rumpus<D> {
 	pub data: Option<D>,
-	// pub meta: HashMap<???>,
+	///Metadata about the request, which Rumpus includes in every response but doesn't document
+	pub meta: Option<Extra>,
 	pub message: Option<String>,
 	pub location: Option<String>,
 	// pub errors: Vec<???>,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+///Information about the delegation key used to make a request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all="camelCase")]
 pub struct DelegationKeyInfo {
-	user_id: String,
-	pass_id: String,
-	permissions: Vec<String>,
+	///The user that created the key
+	pub user_id: String,
+	pub pass_id: String,
+	///What the key is allowed to do
+	pub permissions: HashSet<Permission>,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl DelegationKeyInfo {
+	///Whether this key has the given permission
+	pub fn can(&self, permission: impl Into<Permission>) -> bool {
+		self.permissions.contains(&permission.into())
+	}
+}
+
+/**
+Something a delegation key can be allowed to do, by the name the API uses for it.
+
+Rumpus doesn't document which permissions exist, so this doesn't list them: compare with the names real keys report.
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(transparent)]
+pub struct Permission(String);
+
+impl Permission {
+	pub fn new(name: impl Into<String>) -> Self {
+		Self(name.into())
+	}
+	
+	///The name of this permission as used by the API
+	pub fn name(&self) -> &str {
+		&self.0
+	}
+}
+
+impl From<String> for Permission {
+	fn from(name: String) -> Self {
+		Self(name)
+	}
+}
+
+impl From<&str> for Permission {
+	fn from(name: &str) -> Self {
+		Self(name.to_owned())
+	}
+}
+
+impl From<Permission> for String {
+	fn from(permission: Permission) -> Self {
+		permission.0
+	}
+}
+
+impl fmt::Display for Permission {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{}", self.name())
+	}
 }
 
 ///Since users have *two* Levelhead aliases (one random, one user-chosen), it's useful to be able to differentiate.
 ///These are `levelhead` for user-chosen, and `levelhead-safe` for server-chosen aliases.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[serde(rename_all="kebab-case")]
 pub enum AliasType {
 	Levelhead,
@@ -30,7 +101,7 @@ pub enum AliasType {
 
 //Specified at https://www.bscotch.net/api/docs/levelhead/#aliases-alias-reporting-post
 ///A player's name
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all="camelCase")]
 pub struct Alias {
 	///Synonymous with "Rumpus Lookup Code".
@@ -43,6 +114,106 @@ pub struct Alias {
 	///If a player's username is not found, this field will exist and be set to `true`.
 	///This happens when a user has not yet played Levelhead, or if they've deleted their Rumpus account.
 	pub anonymous: Option<bool>,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl Alias {
+	pub fn is_anonymous(&self) -> bool {
+		self.anonymous == Some(true)
+	}
+}
+
+/**
+Fields of a response this crate doesn't know about, kept so they don't get lost.
+
+See [unknown](crate::unknown) to get notified about them, or to reject them.
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+#[serde(transparent)]
+pub struct Extra(pub ExtraMap);
+
+impl Deref for Extra {
+	type Target = ExtraMap;
+	
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl DerefMut for Extra {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+///Only hashes the field names, as JSON values can't be hashed
+impl Hash for Extra {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		for name in self.0.keys() {
+			name.hash(state);
+		}
+	}
+}
+
+/**
+An [f32] that compares and hashes by its bit pattern, so types containing it can be [Eq] and [Hash].
+
+This means `NaN` equals itself, but `0.0` and `-0.0` are different.
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(transparent)]
+pub struct Float(pub f32);
+
+impl Float {
+	pub fn get(self) -> f32 {
+		self.0
+	}
+}
+
+impl PartialEq for Float {
+	fn eq(&self, other: &Self) -> bool {
+		self.0.to_bits() == other.0.to_bits()
+	}
+}
+
+impl Eq for Float {}
+
+impl Hash for Float {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.0.to_bits().hash(state);
+	}
+}
+
+impl PartialOrd for Float {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Float {
+	fn cmp(&self, other: &Self) -> Ordering {
+		self.0.total_cmp(&other.0)
+	}
+}
+
+impl From<f32> for Float {
+	fn from(value: f32) -> Self {
+		Self(value)
+	}
+}
+
+impl From<Float> for f32 {
+	fn from(value: Float) -> Self {
+		value.0
+	}
+}
+
+impl fmt::Display for Float {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.0.fmt(f)
+	}
 }
 
 /**
@@ -53,7 +224,7 @@ An i32 because multiple players such as <https://lvlhd.co/@8mbjmz> managed to ge
 pub type Stat = i32;
 
 ///Various statistics about a player
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="PascalCase")]
 pub struct PlayerStats {
 	//Values with #[serde(default)] are not present in the server response when 0
@@ -94,15 +265,21 @@ pub struct PlayerStats {
 	///Percentage how much of the campaign/training has been completed
 	#[serde(rename="CampaignProg", default)]
 	pub campaign_progress: u8,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="camelCase")]
 pub struct PlayerInteractions {
 	pub following: bool,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all="camelCase")]
 pub struct Player {
 	#[serde(rename="_id")]
@@ -113,19 +290,42 @@ pub struct Player {
 	pub updated_at: String,
 	pub interactions: Option<PlayerInteractions>,
 	pub stats: PlayerStats,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Player {
+	/**
+	If the account of this player appears to have been deleted.
+	
+	Rumpus keeps the profiles of deleted accounts, but their alias becomes anonymous.
+	Always `false` if the player was fetched without aliases.
+	*/
+	pub fn appears_deleted(&self) -> bool {
+		self.alias.as_ref().is_some_and(Alias::is_anonymous)
+	}
+}
+
+///An entry on a level leaderboard
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all="camelCase")]
 pub struct Record {
-	user_id: String,
-	alias: Alias,
-	value: f32,
-	created_at: String,
+	///The player that set this record
+	pub user_id: String,
+	///Only present when aliases were requested
+	pub alias: Option<Alias>,
+	///The score, or the time in seconds, depending on the leaderboard
+	pub value: Float,
+	///When the record was set
+	pub created_at: String,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
 ///Indicator how much objects of certain categories this level has
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="PascalCase")]
 pub struct LevelContents {
 	pub world: Stat,
@@ -133,9 +333,12 @@ pub struct LevelContents {
 	pub puzzles: Stat,
 	pub enemies: Stat,
 	pub hazards: Stat,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="PascalCase")]
 pub struct LevelStats {
 	#[serde(default)]
@@ -151,37 +354,86 @@ pub struct LevelStats {
 	pub players: Stat,
 	#[serde(default)]
 	pub replay_value: Stat,
-	pub clear_rate: f32,
+	pub clear_rate: Float,
 	///Difficulty diamonds. 6 for uncleared.
 	//TODO: THis would probably be better as an enum
 	pub diamonds: u8,
 	#[serde(default)]
 	pub successes: Stat,
-	pub time_per_win: f32,
+	pub time_per_win: Float,
 	#[serde(default)]
 	pub exposure_bucks: Stat,
-	pub failure_rate: f32,
+	pub failure_rate: Float,
 	
 	#[cfg(feature="undocumented")]
 	pub hidden_gem: Stat,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+impl LevelStats {
+	///The [diamonds](Self::diamonds) of levels nobody has cleared yet
+	pub const UNCLEARED: u8 = 6;
+	
+	pub fn is_cleared(&self) -> bool {
+		self.diamonds != Self::UNCLEARED
+	}
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+///The leaderboards of a level
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all="PascalCase")]
 pub struct LevelRecords {
-	high_score: Vec<Record>,
-	fastest_time: Vec<Record>,
+	///Best first
+	pub high_score: Vec<Record>,
+	///Fastest first
+	pub fastest_time: Vec<Record>,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+///The kinds of leaderboard a level has
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all="PascalCase")]
+pub enum RecordType {
+	HighScore,
+	FastestTime,
+}
+
+impl LevelRecords {
+	///The leaderboard of the given type, best record first
+	pub fn get(&self, record_type: RecordType) -> &[Record] {
+		match record_type {
+			RecordType::HighScore => &self.high_score,
+			RecordType::FastestTime => &self.fastest_time,
+		}
+	}
+	
+	///All records with their type and rank on their leaderboard (0 being the world record)
+	pub fn iter(&self) -> impl Iterator<Item = (RecordType, usize, &Record)> {
+		[RecordType::HighScore, RecordType::FastestTime].into_iter()
+			.flat_map(move |record_type| {
+				self.get(record_type).iter()
+					.enumerate()
+					.map(move |(rank, record)| (record_type, rank, record))
+			})
+	}
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
 #[serde(default)]
 pub struct LevelInteractions {
 	pub bookmarked: bool,
 	pub liked: bool,
 	pub favorited: bool,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all="camelCase")]
 pub struct Level {
 	#[serde(rename="_id")]
@@ -202,7 +454,7 @@ pub struct Level {
 	pub tower_trial: bool,
 	pub required_players: u8,
 	///In seconds. Appears to have 2 significant decimals.
-	pub creator_time: f32,
+	pub creator_time: Float,
 	pub game_version: Option<String>,
 	pub tags: Vec<String>,
 	pub tag_names: Vec<String>,
@@ -210,4 +462,45 @@ pub struct Level {
 	pub stats: Option<LevelStats>,
 	pub records: Option<LevelRecords>,
 	pub interactions: Option<LevelInteractions>,
+	
+	#[serde(flatten)]
+	pub extra: Extra,
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	
+	#[test]
+	fn permission_names() -> Result<(), serde_json::Error> {
+		let permissions = serde_json::from_str::<Vec<Permission>>(r#"["some-permission"]"#)?;
+		assert_eq!(permissions, [Permission::new("some-permission")]);
+		assert_eq!(permissions[0].name(), "some-permission");
+		assert_eq!(serde_json::to_string(&permissions)?, r#"["some-permission"]"#);
+		Ok(())
+	}
+	
+	#[test]
+	fn float_eq() {
+		assert_eq!(Float(f32::NAN), Float(f32::NAN));
+		assert_ne!(Float(0.0), Float(-0.0));
+		assert!(Float(-0.0) < Float(0.0));
+		
+		let set = HashSet::from([Float(1.5), Float(1.5), Float(2.0)]);
+		assert_eq!(set.len(), 2);
+	}
+	
+	#[test]
+	fn key_can() {
+		let info = DelegationKeyInfo {
+			permissions: HashSet::from([Permission::new("some-permission")]),
+			..crate::fixture::key_info("m7n6j8")
+		};
+		
+		assert!(info.can("some-permission"));
+		assert!(info.can(Permission::new("some-permission")));
+		assert!(!info.can("other-permission"));
+	}
 }
\ No newline at end of file
@@ -0,0 +1,206 @@
+/*!
+
+Detecting fields Rumpus CE returns that this crate doesn't know about.
+
+Every Rumpus type collects the fields it doesn't know in its `extra` field, so parsing doesn't break when the API adds something.
+A [FieldPolicy] decides what happens after parsing when there are such fields:
+ignore them, report them to a callback, or fail.
+This way changes to the API can be noticed early without breaking anything in production.
+
+Note that without the `undocumented` feature, the undocumented fields end up in `extra` as well.
+
+*/
+
+use std::{fmt, sync::Arc};
+use serde_json::Value;
+use thiserror::Error;
+use super::types::*;
+
+///A field that isn't known to this crate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+	///Where in the response the field is, e.g. `data[3].stats.NewStat`
+	pub path: String,
+	pub value: Value,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("response contains unknown fields: {}", .fields.iter().map(|f| f.path.as_str()).collect::<Vec<_>>().join(", "))]
+pub struct UnknownFieldsError {
+	pub fields: Vec<UnknownField>,
+}
+
+///What to do with unknown fields
+#[derive(Clone, Default)]
+pub enum FieldPolicy {
+	///Keep them in `extra` without mentioning them
+	#[default]
+	Lenient,
+	///Call the callback for every unknown field
+	Report(Arc<dyn Fn(&UnknownField) + Send + Sync>),
+	///Fail with an [UnknownFieldsError]
+	Strict,
+}
+
+impl FieldPolicy {
+	pub fn report(callback: impl Fn(&UnknownField) + Send + Sync + 'static) -> Self {
+		Self::Report(Arc::new(callback))
+	}
+	
+	/**
+	Applies this policy to a parsed response.
+	
+	# Errors
+	If the policy is [Strict](Self::Strict) and there are unknown fields.
+	*/
+	pub fn check<T: UnknownFields + ?Sized>(&self, value: &T) -> Result<(), UnknownFieldsError> {
+		match self {
+			Self::Lenient => Ok(()),
+			Self::Report(callback) => {
+				value.unknown_fields().iter().for_each(|field| callback(field));
+				Ok(())
+			},
+			Self::Strict => {
+				let fields = value.unknown_fields();
+				if fields.is_empty() {
+					Ok(())
+				} else {
+					Err(UnknownFieldsError { fields })
+				}
+			},
+		}
+	}
+}
+
+impl fmt::Debug for FieldPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Self::Lenient => write!(f, "Lenient"),
+			Self::Report(_) => write!(f, "Report(..)"),
+			Self::Strict => write!(f, "Strict"),
+		}
+	}
+}
+
+///Types that can contain fields this crate doesn't know about
+pub trait UnknownFields {
+	///Adds the unknown fields to `found`, with their paths starting with `path`
+	fn collect_unknown(&self, path: &str, found: &mut Vec<UnknownField>);
+	
+	///All unknown fields in this value, including nested ones
+	fn unknown_fields(&self) -> Vec<UnknownField> {
+		let mut found = Vec::new();
+		self.collect_unknown("", &mut found);
+		found
+	}
+}
+
+fn join(path: &str, name: &str) -> String {
+	if path.is_empty() {
+		name.to_owned()
+	} else {
+		format!("{path}.{name}")
+	}
+}
+
+impl UnknownFields for Extra {
+	fn collect_unknown(&self, path: &str, found: &mut Vec<UnknownField>) {
+		for (name, value) in self.iter() {
+			found.push(UnknownField {
+				path: join(path, name),
+				value: value.clone(),
+			});
+		}
+	}
+}
+
+impl<T: UnknownFields> UnknownFields for Option<T> {
+	fn collect_unknown(&self, path: &str, found: &mut Vec<UnknownField>) {
+		if let Some(value) = self {
+			value.collect_unknown(path, found);
+		}
+	}
+}
+
+impl<T: UnknownFields> UnknownFields for Vec<T> {
+	fn collect_unknown(&self, path: &str, found: &mut Vec<UnknownField>) {
+		for (i, value) in self.iter().enumerate() {
+			value.collect_unknown(&format!("{path}[{i}]"), found);
+		}
+	}
+}
+
+///Implements [UnknownFields] for a type with an `extra` field and the given nested fields
+macro_rules! unknown_fields {
+	($type:ident $(<$generic:ident>)? $(, $nested:ident)*) => {
+		impl$(<$generic: UnknownFields>)? UnknownFields for $type$(<$generic>)? {
+			fn collect_unknown(&self, path: &str, found: &mut Vec<UnknownField>) {
+				self.extra.collect_unknown(path, found);
+				$(
+					self.$nested.collect_unknown(&join(path, stringify!($nested)), found);
+				)*
+			}
+		}
+	};
+}
+
+unknown_fields!(Rumpus<D>, data);
+unknown_fields!(DelegationKeyInfo);
+unknown_fields!(Alias);
+unknown_fields!(PlayerStats);
+unknown_fields!(PlayerInteractions);
+unknown_fields!(Player, alias, interactions, stats);
+unknown_fields!(Record, alias);
+unknown_fields!(LevelContents);
+unknown_fields!(LevelStats);
+unknown_fields!(LevelRecords, high_score, fastest_time);
+unknown_fields!(LevelInteractions);
+unknown_fields!(Level, alias, content, stats, records, interactions);
+
+
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex;
+	use super::*;
+	
+	const PLAYERS: &str = r#"{
+		"data": [{
+			"_id": "5c7715223116090016409e56",
+			"userId": "bscotch119",
+			"createdAt": "2019-02-28T00:00:00.000Z",
+			"updatedAt": "2023-04-01T00:00:00.000Z",
+			"stats": {"Subscribers": 1, "PlayTime": 2, "Crowns": 3, "Shoes": 4, "NumFollowing": 5, "NewStat": 6},
+			"badge": "gold"
+		}],
+		"meta": {}
+	}"#;
+	
+	#[test]
+	fn collects_paths() {
+		let players = serde_json::from_str::<Rumpus<Vec<Player>>>(PLAYERS).unwrap();
+		let paths = players.unknown_fields().into_iter().map(|f| f.path).collect::<Vec<_>>();
+		
+		//Every response has meta, so it has to be known
+		assert_eq!(paths, ["data[0].badge", "data[0].stats.NewStat"]);
+	}
+	
+	#[test]
+	fn policies() {
+		let players = serde_json::from_str::<Rumpus<Vec<Player>>>(PLAYERS).unwrap();
+		
+		assert!(FieldPolicy::Lenient.check(&players).is_ok());
+		
+		let reported = Arc::new(Mutex::new(Vec::new()));
+		let policy = FieldPolicy::report({
+			let reported = reported.clone();
+			move |field| reported.lock().unwrap().push(field.path.clone())
+		});
+		assert!(policy.check(&players).is_ok());
+		assert_eq!(reported.lock().unwrap().len(), 2);
+		
+		let err = FieldPolicy::Strict.check(&players).unwrap_err();
+		assert_eq!(err.fields.len(), 2);
+		assert_eq!(err.fields[1].value, Value::from(6));
+	}
+}
@@ -0,0 +1,649 @@
+/*!
+
+Watching for new levels and stat changes.
+
+A [LevelWatcher] periodically runs a level search, newest first, and hands out the levels it hasn't seen before as [NewLevel] events.
+It remembers how far it got in a [WatchCursor]:
+the creation date of the newest level it saw, and the ids of the levels created at exactly that moment.
+Searches only ask for levels created at or after that date and page through all of them,
+so levels aren't missed or repeated when more of them get published between polls than fit on a page.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use std::time::Duration;
+use futures_util::StreamExt;
+use rumpus_ce::{client::RumpusClient, query::LevelSearch, watch::LevelWatcher};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+let mut new_levels = LevelWatcher::new(client, LevelSearch::new())
+	.interval(Duration::from_secs(60))
+	.creators(["a-followed-user-id"])
+	.into_stream();
+while let Some(new) = new_levels.next().await {
+	println!("new level: {}", new?.level.title);
+}
+# Ok(())
+# }
+```
+
+The first poll only looks up the newest levels, so only levels published after the watcher started are reported.
+Use [LevelWatcher::resume] with a saved [cursor](LevelWatcher::cursor) to continue where a previous watcher stopped,
+or [LevelWatcher::since] to also report older levels.
+
+Don't give the client a [response cache](crate::cache): it would serve the same results to every poll.
+
+# Stat changes
+
+A [StatWatcher] tracks a fixed set of levels and players, and reports the thresholds they crossed since its previous poll as [StatEvent]s:
+first clears, new fastest times, subscriber milestones and crowns gained.
+The tracked levels are looked up with their stats and records, up to [LevelSearch::MAX_LEVELS] per request,
+and the tracked players up to [PlayerSearch::MAX_USERS] per request.
+
+```no_run
+# async fn example() -> Result<(), rumpus_ce::client::ClientError> {
+use futures_util::StreamExt;
+use rumpus_ce::{client::RumpusClient, watch::{StatEvent, StatWatcher}};
+
+let client = RumpusClient::with_key("your-delegation-key")?;
+let mut events = StatWatcher::new(client)
+	.track_levels(["a-level-id"])
+	.track_players(["a-user-id"])
+	.into_stream();
+while let Some(event) = events.next().await {
+	if let StatEvent::SubscriberMilestone { user_id, milestone, .. } = event? {
+		println!("{user_id} reached {milestone} subscribers");
+	}
+}
+# Ok(())
+# }
+```
+
+Like with new levels, the first poll only remembers the current stats.
+
+*/
+
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	pin::Pin,
+	time::{Duration, SystemTime},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::time::{self, MissedTickBehavior};
+use super::{
+	client::{ClientError, RumpusClient},
+	query::{LevelSearch, PlayerSearch},
+	snapshots::Stats,
+	types::{Float, Level, Player, Record, RecordType, Stat},
+};
+
+///A level the [LevelWatcher] hadn't seen before
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewLevel {
+	pub level: Level,
+	///When the watcher found it
+	pub found_at: SystemTime,
+}
+
+///How far a [LevelWatcher] got, can be saved to [resume](LevelWatcher::resume) later
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all="camelCase")]
+pub struct WatchCursor {
+	///The creation date of the newest level seen, levels created before it are considered seen
+	pub created_at: String,
+	///The ids of the levels seen that were created at exactly [Self::created_at]
+	pub ids: Vec<String>,
+}
+
+impl WatchCursor {
+	///Everything created at or after `created_at` counts as unseen
+	pub fn since(created_at: impl Into<String>) -> Self {
+		Self {
+			created_at: created_at.into(),
+			ids: Vec::new(),
+		}
+	}
+	
+	pub fn has_seen(&self, level: &Level) -> bool {
+		//The dates are ISO 8601 in UTC, so they sort as strings
+		level.created_at < self.created_at || (level.created_at == self.created_at && self.ids.contains(&level.id))
+	}
+	
+	///Marks a level as seen
+	pub fn advance(&mut self, level: &Level) {
+		if level.created_at > self.created_at {
+			self.created_at = level.created_at.clone();
+			self.ids = vec![level.id.clone()];
+		} else if level.created_at == self.created_at && !self.ids.contains(&level.id) {
+			self.ids.push(level.id.clone());
+		}
+	}
+}
+
+///Polls for new levels, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct LevelWatcher {
+	client: RumpusClient,
+	search: LevelSearch,
+	interval: Duration,
+	cursor: Option<WatchCursor>,
+	tags: Vec<String>,
+	creators: HashSet<String>,
+	min_diamonds: Option<u8>,
+	max_diamonds: Option<u8>,
+}
+
+impl LevelWatcher {
+	pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+	
+	///Watches for new levels `search` finds. The sort and paging parameters of the search are overwritten.
+	pub fn new(client: RumpusClient, search: LevelSearch) -> Self {
+		Self {
+			client,
+			search,
+			interval: Self::DEFAULT_INTERVAL,
+			cursor: None,
+			tags: Vec::new(),
+			creators: HashSet::new(),
+			min_diamonds: None,
+			max_diamonds: None,
+		}
+	}
+	
+	///How long to wait between polls of the [stream](Self::into_stream)
+	pub fn interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+	
+	///Continue where a previous watcher stopped
+	pub fn resume(mut self, cursor: WatchCursor) -> Self {
+		self.cursor = Some(cursor);
+		self
+	}
+	
+	///Also report the levels created at or after `created_at`, instead of only the ones published after the first poll
+	pub fn since(self, created_at: impl Into<String>) -> Self {
+		self.resume(WatchCursor::since(created_at))
+	}
+	
+	/**
+	Only report levels with at least one of these tags, either the tag id (`ltag_short`) or its name.
+	
+	A single tag id is also passed on to the search, as Rumpus can only filter on one.
+	*/
+	pub fn tags<S: Into<String>>(mut self, tags: impl IntoIterator<Item = S>) -> Self {
+		self.tags = tags.into_iter().map(Into::into).collect();
+		self
+	}
+	
+	/**
+	Only report levels made by the players with these user ids.
+	
+	Up to [LevelSearch::MAX_USERS] of them are also passed on to the search,
+	which then also finds their levels that aren't in the tower.
+	*/
+	pub fn creators<S: Into<String>>(mut self, user_ids: impl IntoIterator<Item = S>) -> Self {
+		self.creators = user_ids.into_iter().map(Into::into).collect();
+		self
+	}
+	
+	///Only report levels with at least this many diamonds. Makes the search include stats.
+	pub fn min_diamonds(mut self, diamonds: u8) -> Self {
+		self.min_diamonds = Some(diamonds);
+		self
+	}
+	
+	///Only report levels with at most this many diamonds. Makes the search include stats.
+	pub fn max_diamonds(mut self, diamonds: u8) -> Self {
+		self.max_diamonds = Some(diamonds);
+		self
+	}
+	
+	///How far the watcher got, `None` before the first poll
+	pub fn cursor(&self) -> Option<&WatchCursor> {
+		self.cursor.as_ref()
+	}
+	
+	///Whether a level passes the filters
+	pub fn matches(&self, level: &Level) -> bool {
+		let tagged = self.tags.is_empty() || level.tags.iter().chain(&level.tag_names).any(|tag| self.tags.contains(tag));
+		let by_creator = self.creators.is_empty() || self.creators.contains(&level.user_id);
+		let diamonds = level.stats.as_ref().map(|stats| stats.diamonds);
+		let min = self.min_diamonds.is_none_or(|min| diamonds.is_some_and(|diamonds| diamonds >= min));
+		let max = self.max_diamonds.is_none_or(|max| diamonds.is_some_and(|diamonds| diamonds <= max));
+		tagged && by_creator && min && max
+	}
+	
+	fn prepare(&self, cursor: Option<&WatchCursor>) -> LevelSearch {
+		let mut search = self.search.clone().newest_first();
+		search.tiebreaker_item_id = None;
+		search.max_created_at = None;
+		search.min_created_at = cursor
+			.map(|cursor| cursor.created_at.clone())
+			.filter(|created_at| !created_at.is_empty());
+		if search.limit.is_none() {
+			search.limit = Some(LevelSearch::MAX_LIMIT as u8);
+		}
+		let diamonds = self.min_diamonds.is_some() || self.max_diamonds.is_some();
+		if diamonds && search.include_stats.is_none() {
+			search.include_stats = Some(true);
+		}
+		//Let Rumpus filter when it can, [Self::matches] still checks the results
+		if search.user_ids.is_none() && !self.creators.is_empty() && self.creators.len() <= LevelSearch::MAX_USERS {
+			let mut user_ids = self.creators.iter().cloned().collect::<Vec<_>>();
+			user_ids.sort_unstable();
+			search.user_ids = Some(user_ids);
+		}
+		if let ([tag], None) = (self.tags.as_slice(), &search.tags) {
+			//Names can't be searched for
+			if tag.starts_with("ltag_") {
+				search.tags = Some(tag.clone());
+			}
+		}
+		search
+	}
+	
+	/**
+	Looks for new levels once, and returns the ones that pass the filters, oldest first.
+	
+	The first poll without a cursor only looks up the newest levels and returns nothing.
+	
+	# Errors
+	If a request failed. The cursor isn't moved, so the next poll tries again.
+	*/
+	pub async fn poll(&mut self) -> Result<Vec<NewLevel>, ClientError> {
+		let first = self.cursor.is_none();
+		let mut cursor = match &self.cursor {
+			Some(cursor) => cursor.clone(),
+			None => {
+				let newest = self.client.levels(self.prepare(None).limit(1)?).await?;
+				match newest.first() {
+					//Other levels might have been created at the same moment
+					Some(level) => WatchCursor::since(level.created_at.clone()),
+					None => WatchCursor::default(),
+				}
+			},
+		};
+		
+		let mut unseen = Vec::new();
+		let mut pages = self.client.pages(self.prepare(Some(&cursor)));
+		while let Some(page) = pages.next().await {
+			unseen.extend(page?.into_iter().filter(|level| !cursor.has_seen(level)));
+		}
+		drop(pages);
+		
+		for level in &unseen {
+			cursor.advance(level);
+		}
+		self.cursor = Some(cursor);
+		if first {
+			return Ok(Vec::new());
+		}
+		
+		let found_at = SystemTime::now();
+		Ok(unseen.into_iter()
+			.rev()
+			.filter(|level| self.matches(level))
+			.map(|level| NewLevel { level, found_at })
+			.collect())
+	}
+	
+	/**
+	Polls every [interval](Self::interval), starting right away, and hands out the new levels one by one.
+	
+	Failed polls are handed out as errors, after which the stream continues with the next poll.
+	*/
+	pub fn into_stream(self) -> Pin<Box<dyn Stream<Item = Result<NewLevel, ClientError>> + Send>> {
+		let mut interval = time::interval(self.interval);
+		interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		let state = (self, interval, VecDeque::new());
+		Box::pin(stream::unfold(state, |(mut watcher, mut interval, mut found)| async move {
+			loop {
+				if let Some(new) = found.pop_front() {
+					return Some((Ok(new), (watcher, interval, found)));
+				}
+				interval.tick().await;
+				match watcher.poll().await {
+					Ok(new) => found.extend(new),
+					Err(err) => return Some((Err(err), (watcher, interval, found))),
+				}
+			}
+		}))
+	}
+}
+
+
+///A threshold a tracked level or player crossed between two polls of a [StatWatcher]
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatEvent {
+	///Somebody cleared the level for the first time, so its diamonds are no longer [uncleared](crate::types::LevelStats::UNCLEARED)
+	FirstClear {
+		level_id: String,
+		diamonds: u8,
+	},
+	///The level got a new fastest time, or its first one
+	FastestTime {
+		level_id: String,
+		record: Record,
+		///The time of the previous fastest record, in seconds
+		previous: Option<Float>,
+	},
+	///The player reached a subscriber milestone. Only the highest one is reported when several were passed at once.
+	SubscriberMilestone {
+		user_id: String,
+		milestone: Stat,
+		subscribers: Stat,
+	},
+	///The player gained crowns
+	CrownsGained {
+		user_id: String,
+		gained: i64,
+		crowns: Stat,
+	},
+}
+
+impl StatEvent {
+	///The level id or user id the event is about
+	pub fn id(&self) -> &str {
+		match self {
+			Self::FirstClear { level_id, .. } | Self::FastestTime { level_id, .. } => level_id,
+			Self::SubscriberMilestone { user_id, .. } | Self::CrownsGained { user_id, .. } => user_id,
+		}
+	}
+}
+
+fn level_events(earlier: &Level, later: &Level) -> Vec<StatEvent> {
+	let mut events = Vec::new();
+	if let (Some(earlier), Some(stats)) = (&earlier.stats, &later.stats) {
+		if !earlier.is_cleared() && stats.is_cleared() {
+			events.push(StatEvent::FirstClear {
+				level_id: later.level_id.clone(),
+				diamonds: stats.diamonds,
+			});
+		}
+	}
+	if let (Some(earlier), Some(records)) = (&earlier.records, &later.records) {
+		let previous = earlier.get(RecordType::FastestTime).first().map(|record| record.value);
+		if let Some(record) = records.get(RecordType::FastestTime).first() {
+			if previous.is_none_or(|previous| record.value.get() < previous.get()) {
+				events.push(StatEvent::FastestTime {
+					level_id: later.level_id.clone(),
+					record: record.clone(),
+					previous,
+				});
+			}
+		}
+	}
+	events
+}
+
+fn player_events(earlier: &Player, later: &Player, milestones: &[Stat]) -> Vec<StatEvent> {
+	let mut events = Vec::new();
+	let (before, subscribers) = (earlier.stats.subscribers, later.stats.subscribers);
+	if let Some(&milestone) = milestones.iter().rev().find(|&&milestone| before < milestone && milestone <= subscribers) {
+		events.push(StatEvent::SubscriberMilestone {
+			user_id: later.user_id.clone(),
+			milestone,
+			subscribers,
+		});
+	}
+	let delta = later.stats.delta_since(&earlier.stats);
+	if delta.crowns > 0 {
+		events.push(StatEvent::CrownsGained {
+			user_id: later.user_id.clone(),
+			gained: delta.crowns,
+			crowns: later.stats.crowns,
+		});
+	}
+	events
+}
+
+///Polls the stats of a set of levels and players, and reports the thresholds they crossed, see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct StatWatcher {
+	client: RumpusClient,
+	level_ids: Vec<String>,
+	user_ids: Vec<String>,
+	interval: Duration,
+	subscriber_milestones: Vec<Stat>,
+	levels: HashMap<String, Level>,
+	players: HashMap<String, Player>,
+}
+
+impl StatWatcher {
+	pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(300);
+	pub const DEFAULT_SUBSCRIBER_MILESTONES: [Stat; 10] = [10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+	
+	///Tracks nothing yet, add levels and players with [Self::track_levels] and [Self::track_players]
+	pub fn new(client: RumpusClient) -> Self {
+		Self {
+			client,
+			level_ids: Vec::new(),
+			user_ids: Vec::new(),
+			interval: Self::DEFAULT_INTERVAL,
+			subscriber_milestones: Self::DEFAULT_SUBSCRIBER_MILESTONES.to_vec(),
+			levels: HashMap::new(),
+			players: HashMap::new(),
+		}
+	}
+	
+	///Also track the levels with these level ids (a.k.a. "share codes")
+	pub fn track_levels<S: Into<String>>(mut self, level_ids: impl IntoIterator<Item = S>) -> Self {
+		for level_id in level_ids {
+			let level_id = level_id.into();
+			if !self.level_ids.contains(&level_id) {
+				self.level_ids.push(level_id);
+			}
+		}
+		self
+	}
+	
+	///Also track the players with these user ids
+	pub fn track_players<S: Into<String>>(mut self, user_ids: impl IntoIterator<Item = S>) -> Self {
+		for user_id in user_ids {
+			let user_id = user_id.into();
+			if !self.user_ids.contains(&user_id) {
+				self.user_ids.push(user_id);
+			}
+		}
+		self
+	}
+	
+	///How long to wait between polls of the [stream](Self::into_stream)
+	pub fn interval(mut self, interval: Duration) -> Self {
+		self.interval = interval;
+		self
+	}
+	
+	///The subscriber counts to report, instead of [Self::DEFAULT_SUBSCRIBER_MILESTONES]
+	pub fn subscriber_milestones(mut self, milestones: impl IntoIterator<Item = Stat>) -> Self {
+		self.subscriber_milestones = milestones.into_iter().collect();
+		self.subscriber_milestones.sort_unstable();
+		self.subscriber_milestones.dedup();
+		self
+	}
+	
+	///The tracked level as of the last poll, to give events some context
+	pub fn level(&self, level_id: &str) -> Option<&Level> {
+		self.levels.get(level_id)
+	}
+	
+	///The tracked player as of the last poll, to give events some context
+	pub fn player(&self, user_id: &str) -> Option<&Player> {
+		self.players.get(user_id)
+	}
+	
+	async fn fetch_levels(&self) -> Result<Vec<Level>, ClientError> {
+		let mut levels = Vec::with_capacity(self.level_ids.len());
+		for level_ids in self.level_ids.chunks(LevelSearch::MAX_LEVELS) {
+			let search = LevelSearch::new()
+				.level_ids(level_ids)?
+				.limit(level_ids.len() as u8)?
+				.include_stats(true)
+				.include_records(true);
+			levels.extend(self.client.levels(search).await?);
+		}
+		Ok(levels)
+	}
+	
+	async fn fetch_players(&self) -> Result<Vec<Player>, ClientError> {
+		let mut players = Vec::with_capacity(self.user_ids.len());
+		for user_ids in self.user_ids.chunks(PlayerSearch::MAX_USERS) {
+			let search = PlayerSearch::new()
+				.user_ids(user_ids)?
+				.limit(user_ids.len() as u8)?;
+			players.extend(self.client.players(search).await?);
+		}
+		Ok(players)
+	}
+	
+	/**
+	Looks up the tracked levels and players once, and returns what changed since the previous poll.
+	
+	Levels and players seen for the first time (e.g. on the first poll) only get remembered, and don't produce events.
+	Ones that couldn't be found, e.g. because they got deleted, are skipped.
+	
+	# Errors
+	If a request failed. Nothing gets remembered then, so the next poll compares against the same stats.
+	*/
+	pub async fn poll(&mut self) -> Result<Vec<StatEvent>, ClientError> {
+		let levels = self.fetch_levels().await?;
+		let players = self.fetch_players().await?;
+		
+		let mut events = Vec::new();
+		for level in levels {
+			if let Some(earlier) = self.levels.get(&level.level_id) {
+				events.extend(level_events(earlier, &level));
+			}
+			self.levels.insert(level.level_id.clone(), level);
+		}
+		for player in players {
+			if let Some(earlier) = self.players.get(&player.user_id) {
+				events.extend(player_events(earlier, &player, &self.subscriber_milestones));
+			}
+			self.players.insert(player.user_id.clone(), player);
+		}
+		Ok(events)
+	}
+	
+	/**
+	Polls every [interval](Self::interval), starting right away, and hands out the events one by one.
+	
+	Failed polls are handed out as errors, after which the stream continues with the next poll.
+	*/
+	pub fn into_stream(self) -> Pin<Box<dyn Stream<Item = Result<StatEvent, ClientError>> + Send>> {
+		let mut interval = time::interval(self.interval);
+		interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+		let state = (self, interval, VecDeque::new());
+		Box::pin(stream::unfold(state, |(mut watcher, mut interval, mut found)| async move {
+			loop {
+				if let Some(event) = found.pop_front() {
+					return Some((Ok(event), (watcher, interval, found)));
+				}
+				interval.tick().await;
+				match watcher.poll().await {
+					Ok(events) => found.extend(events),
+					Err(err) => return Some((Err(err), (watcher, interval, found))),
+				}
+			}
+		}))
+	}
+}
+
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{fixture::{self, player_with, record}, types::*};
+	
+	fn level(id: &str, created_at: &str) -> Level {
+		Level {
+			id: id.into(),
+			level_id: id.into(),
+			created_at: created_at.into(),
+			stats: None,
+			..fixture::level(0)
+		}
+	}
+	
+	#[test]
+	fn cursor() {
+		let mut cursor = WatchCursor::since("2023-01-01T00:00:00.000Z");
+		let older = level("older", "2022-12-31T23:59:59.999Z");
+		let tied = level("tied", "2023-01-01T00:00:00.000Z");
+		let other_tied = level("other", "2023-01-01T00:00:00.000Z");
+		let newer = level("newer", "2023-01-02T00:00:00.000Z");
+		
+		assert!(cursor.has_seen(&older));
+		assert!(!cursor.has_seen(&tied));
+		cursor.advance(&tied);
+		assert!(cursor.has_seen(&tied));
+		assert!(!cursor.has_seen(&other_tied));
+		
+		cursor.advance(&newer);
+		cursor.advance(&other_tied);
+		assert_eq!(cursor, WatchCursor { created_at: newer.created_at.clone(), ids: vec!["newer".into()] });
+		assert!(cursor.has_seen(&other_tied));
+	}
+	
+	#[test]
+	fn prepare() -> Result<(), ClientError> {
+		//Never sends anything
+		let client = RumpusClient::with_key("key")?;
+		let watcher = LevelWatcher::new(client, LevelSearch::new())
+			.creators(["b", "a"])
+			.tags(["ltag_short"]);
+		let search = watcher.prepare(None);
+		assert_eq!(search.user_ids, Some(vec!["a".into(), "b".into()]));
+		assert_eq!(search.tags.as_deref(), Some("ltag_short"));
+		
+		//Tag names and more than one tag can only be checked afterwards
+		let search = watcher.clone().tags(["Short"]).prepare(None);
+		assert_eq!(search.tags, None);
+		let search = watcher.clone().tags(["ltag_short", "ltag_long"]).prepare(None);
+		assert_eq!(search.tags, None);
+		
+		let too_many = (0..=LevelSearch::MAX_USERS).map(|n| n.to_string());
+		assert_eq!(watcher.creators(too_many).prepare(None).user_ids, None);
+		
+		Ok(())
+	}
+	
+	#[test]
+	fn stat_events() {
+		let mut earlier = level("code", "2023-01-01T00:00:00.000Z");
+		earlier.stats = Some(LevelStats { diamonds: LevelStats::UNCLEARED, ..Default::default() });
+		earlier.records = Some(LevelRecords::default());
+		assert!(level_events(&earlier, &earlier).is_empty());
+		
+		let mut later = earlier.clone();
+		later.stats = Some(LevelStats { diamonds: 4, ..Default::default() });
+		later.records = Some(LevelRecords { fastest_time: vec![record("user01", 12.5)], ..Default::default() });
+		assert_eq!(level_events(&earlier, &later), [
+			StatEvent::FirstClear { level_id: "code".into(), diamonds: 4 },
+			StatEvent::FastestTime { level_id: "code".into(), record: record("user01", 12.5), previous: None },
+		]);
+		
+		//A slower time doesn't count, and stats that weren't included can't be compared
+		let mut slower = later.clone();
+		slower.records = Some(LevelRecords { fastest_time: vec![record("user01", 12.5), record("user02", 13.0)], ..Default::default() });
+		slower.stats = None;
+		assert!(level_events(&later, &slower).is_empty());
+		let mut faster = later.clone();
+		faster.records = Some(LevelRecords { fastest_time: vec![record("user02", 11.0), record("user01", 12.5)], ..Default::default() });
+		assert_eq!(level_events(&later, &faster), [
+			StatEvent::FastestTime { level_id: "code".into(), record: record("user02", 11.0), previous: Some(Float(12.5)) },
+		]);
+		
+		let milestones = StatWatcher::DEFAULT_SUBSCRIBER_MILESTONES;
+		assert!(player_events(&player_with(0, 9, 3), &player_with(0, 9, 3), &milestones).is_empty());
+		assert!(player_events(&player_with(0, 10, 3), &player_with(0, 24, 2), &milestones).is_empty());
+		assert_eq!(player_events(&player_with(0, 9, 3), &player_with(0, 60, 5), &milestones), [
+			StatEvent::SubscriberMilestone { user_id: "user00".into(), milestone: 50, subscribers: 60 },
+			StatEvent::CrownsGained { user_id: "user00".into(), gained: 2, crowns: 5 },
+		]);
+		assert_eq!(player_events(&player_with(0, 49, 3), &player_with(0, 50, 3), &milestones)[0].id(), "user00");
+	}
+}
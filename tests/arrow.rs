@@ -0,0 +1,71 @@
+use std::fs::File;
+
+use arrow::{
+	array::{Array, AsArray, StructArray},
+	datatypes::{Int32Type, TimestampMillisecondType},
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rumpus_ce::arrow::{level_schema, player_schema, record_batch, ParquetWriter};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::{fixture_levels, fixture_players};
+
+#[test]
+fn batches() -> Result<(), Error> {
+	let levels = fixture_levels()?;
+	let batch = record_batch(&levels)?;
+	assert_eq!(batch.schema(), level_schema());
+	assert_eq!(batch.num_rows(), levels.len());
+	
+	let level_ids = batch.column_by_name("levelId").unwrap().as_string::<i32>();
+	assert_eq!(level_ids.value(0), levels[0].level_id);
+	
+	let stats = batch.column_by_name("stats").unwrap().as_any().downcast_ref::<StructArray>().unwrap();
+	let likes = stats.column_by_name("Likes").unwrap().as_primitive::<Int32Type>();
+	assert_eq!(likes.value(0), levels[0].stats.as_ref().unwrap().likes);
+	
+	let tags = batch.column_by_name("tags").unwrap().as_list::<i32>();
+	assert_eq!(tags.value(0).len(), levels[0].tags.len());
+	
+	let created = batch.column_by_name("createdAt").unwrap().as_primitive::<TimestampMillisecondType>();
+	assert!(created.value(0) > 1_500_000_000_000);
+	
+	//Missing stats become nulls
+	let mut stripped = levels[0].clone();
+	stripped.stats = None;
+	stripped.records = None;
+	let batch = record_batch(&[stripped])?;
+	assert!(batch.column_by_name("stats").unwrap().is_null(0));
+	
+	let players = fixture_players()?;
+	let batch = record_batch(&players)?;
+	assert_eq!(batch.schema(), player_schema());
+	assert_eq!(batch.num_rows(), players.len());
+	
+	Ok(())
+}
+
+#[test]
+fn parquet() -> Result<(), Error> {
+	let levels = fixture_levels()?;
+	let path = std::env::temp_dir().join(format!("rumpus_ce-arrow-{}.parquet", std::process::id()));
+	
+	let mut writer = ParquetWriter::new(File::create(&path)?)?;
+	let written = writer.write_pages_blocking([Ok(levels.clone()), Ok(Vec::new()), Ok(levels.clone())])?;
+	assert_eq!(written, levels.len() * 2);
+	writer.finish()?;
+	
+	let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?.build()?;
+	let mut rows = 0;
+	for batch in reader {
+		let batch = batch?;
+		assert_eq!(batch.schema().fields(), level_schema().fields());
+		rows += batch.num_rows();
+	}
+	assert_eq!(rows, levels.len() * 2);
+	
+	std::fs::remove_file(path)?;
+	Ok(())
+}
@@ -0,0 +1,32 @@
+use rumpus_ce::{
+	blocking::BlockingRumpusClient,
+	fixture,
+	mock::MockServer,
+	query::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::{dataset, KEY};
+
+#[test]
+fn blocks() -> Result<(), Error> {
+	//The server needs a runtime of its own, the client brings its own
+	let runtime = tokio::runtime::Runtime::new()?;
+	let server = runtime.block_on(MockServer::start(dataset().levels((0..7).map(fixture::level))))?;
+	
+	let client = BlockingRumpusClient::with_key_url(KEY, server.url())?;
+	
+	assert_eq!(client.key_info()?.user_id, "user00");
+	assert_eq!(client.level("code03")?.map(|l| l.title).as_deref(), Some("Level 3"));
+	
+	let titles = client.pages(LevelSearch::new().oldest_first().limit(3)?)
+		.map(|page| page.map(|levels| levels.into_iter().map(|l| l.title).collect::<Vec<_>>()))
+		.collect::<Result<Vec<_>, _>>()?;
+	assert_eq!(titles.len(), 3);
+	assert_eq!(titles[0], ["Level 0", "Level 1", "Level 2"]);
+	assert_eq!(titles[2], ["Level 6"]);
+	
+	Ok(())
+}
@@ -1,24 +1,44 @@
-use restson::RestClient;
+/*!
+Checks we can parse what Rumpus CE returns.
+
+These talk to the live API with the delegation key in `key.txt`, so they're ignored by default:
+
+```sh
+cargo test --test can_parse -- --ignored
+```
+
+Set the `RUMPUS_RECORD` environment variable to also save the responses in `tests/fixtures`, see the README there.
+*/
 
 use rumpus_ce::{
+	key::KeySource,
+	replay::{ReplayClient, ReplayError},
 	types::*,
 	query::*,
 };
 
 use anyhow::{Error, Result};
 
-const API_KEY: &str = include_str!("../key.txt");
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
 
-fn client() -> RestClient {
-	rumpus_ce::rest_client::with_key(API_KEY)
+fn client() -> ReplayClient {
+	let key = KeySource::file(concat!(env!("CARGO_MANIFEST_DIR"), "/key.txt"))
+		.load()
+		.expect("these tests need a delegation key in key.txt");
+	let client = rumpus_ce::rest_client::with_key(key.expose());
+	if std::env::var_os("RUMPUS_RECORD").is_some() {
+		ReplayClient::record(client, FIXTURES)
+	} else {
+		ReplayClient::live(client)
+	}
 }
 
-fn err_info<R>(res: Result<R, restson::Error>) -> Result<R, restson::Error> {
+fn err_info<R>(res: Result<R, ReplayError>) -> Result<R, ReplayError> {
 	match res {
 		Ok(res) => Ok(res),
 		Err(err) => {
 			use restson::Error::*;
-			if let DeserializeParseError(ref e, ref s) = err {
+			if let ReplayError::Rest(DeserializeParseError(ref e, ref s)) = err {
 				assert_eq!(e.line(), 1);
 				let col = e.column();
 				let mut char_i = s.char_indices();
@@ -35,24 +55,32 @@ fn err_info<R>(res: Result<R, restson::Error>) -> Result<R, restson::Error> {
 
 ///Test we can fetch & parse info about the current delegation key
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn this_key() {
 	let res = client().get::<_, Rumpus<DelegationKeyInfo>>(()).await;
 	let res = err_info(res);
 	assert!(matches!(res, Result::Ok(_)));
+	let info = res.unwrap().data.expect("no data was returned");
+	
+	assert!(!info.user_id.is_empty());
+	for permission in &info.permissions {
+		assert!(info.can(permission.clone()));
+	}
 }
 
 ///Verify we can fetch and parse the newest 16 players
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn newest() -> Result<(), Error> {
 	let search = PlayerSearch::new()
-		.sort(PlayerSortProperty::CreatedAt, false)
+		.newest_first()
 		.limit(16)?
 		.include_aliases(true)
 		.include_my_interactions(true);
 	
 	let res = client().get::<_, Rumpus<Vec<Player>>>(search).await;
 	let res = err_info(res)?;
-	let data = res.into_inner().data.expect("no data was returned");
+	let data = res.data.expect("no data was returned");
 	
 	//Verify the API returned stuff that will have gotten parsed
 	assert_eq!(data.len(), 16);
@@ -62,16 +90,17 @@ async fn newest() -> Result<(), Error> {
 
 ///Verify we can fetch and parse the oldest 16 players
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn oldest() -> Result<(), Error> {
 	let search = PlayerSearch::new()
-		.sort(PlayerSortProperty::CreatedAt, true)
+		.oldest_first()
 		.limit(16)?
 		.include_aliases(true)
 		.include_my_interactions(true);
 	
 	let res = client().get::<_, Rumpus<Vec<Player>>>(search).await;
 	let res = err_info(res)?;
-	let data = res.into_inner().data.expect("no data was returned");
+	let data = res.data.expect("no data was returned");
 	
 	//Verify the API returned stuff that will have gotten parsed
 	assert_eq!(data.len(), 16);
@@ -83,19 +112,20 @@ async fn oldest() -> Result<(), Error> {
 	Ok(())
 }
 
-///Test that we can parse some special players, and that their special attributes still hold
+///Test that we can parse some special players, and that their special attributes still hold.
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn special() -> Result<(), Error> {
 	let search = PlayerSearch::new()
 		.user_ids(vec!["0ihetl","8mbjmz","pg11x1","bscotch246"])?
 		//Make sure the return order is stable
-		.sort(PlayerSortProperty::CreatedAt, true)
+		.oldest_first()
 		.include_aliases(true)
 		.include_my_interactions(true);
 	
 	let res = client().get::<_, Rumpus<Vec<Player>>>(search).await;
 	let res = err_info(res)?;
-	let data = res.into_inner().data.expect("no data was returned");
+	let data = res.data.expect("no data was returned");
 	
 	// dbg!(&data);
 	
@@ -126,7 +156,8 @@ async fn special() -> Result<(), Error> {
 			alias_type: None,
 			anonymous: Some(true),
 			alias: None,
-			user_id: _
+			user_id: _,
+			extra: _
 		})
 	));
 	
@@ -135,6 +166,7 @@ async fn special() -> Result<(), Error> {
 
 ///Verify we can fetch and parse the newest levels
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn newest_levels() -> Result<(), Error> {
 	let search = LevelSearch::new()
 		.include_aliases(true)
@@ -143,11 +175,11 @@ async fn newest_levels() -> Result<(), Error> {
 		.include_records(true)
 		.include_stats(true)
 		.limit(64)?
-		.sort(LevelSortProperty::CreatedAt, false);
+		.newest_first();
 	
 	let res = client().get::<_, Rumpus<Vec<Level>>>(search).await;
 	let res = err_info(res)?;
-	let data = res.into_inner().data.expect("no data was returned");
+	let data = res.data.expect("no data was returned");
 	
 	//Verify the API returned stuff that will have gotten parsed
 	assert_eq!(data.len(), 64);
@@ -157,6 +189,7 @@ async fn newest_levels() -> Result<(), Error> {
 
 ///Verify we can fetch and parse the newest levels
 #[tokio::test]
+#[ignore = "talks to the live API, needs a delegation key in key.txt"]
 async fn oldest_levels() -> Result<(), Error> {
 	let search = LevelSearch::new()
 		.include_aliases(true)
@@ -165,11 +198,11 @@ async fn oldest_levels() -> Result<(), Error> {
 		.include_records(true)
 		.include_stats(true)
 		.limit(64)?
-		.sort(LevelSortProperty::CreatedAt, true);
+		.oldest_first();
 	
 	let res = client().get::<_, Rumpus<Vec<Level>>>(search).await;
 	let res = err_info(res)?;
-	let data = res.into_inner().data.expect("no data was returned");
+	let data = res.data.expect("no data was returned");
 	
 	//Verify the API returned stuff that will have gotten parsed
 	assert_eq!(data.len(), 64);
@@ -0,0 +1,183 @@
+use futures_util::StreamExt;
+use rumpus_ce::{
+	cache::{Endpoint, ResponseCache},
+	client::{ClientError, RumpusClient},
+	fixture,
+	key::DelegationKey,
+	mock::MockServer,
+	pool::KeyPool,
+	query::*,
+	types::*,
+	unknown::FieldPolicy,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::{Canned, KEY};
+
+const KEY_INFO: &str = r#"{"data": {"userId": "m7n6j8", "passId": "pass", "permissions": []}}"#;
+
+#[tokio::test]
+async fn uses_transport() -> Result<(), Error> {
+	let canned = Canned::default().with("delegation/keys/@this", KEY_INFO);
+	let client = RumpusClient::new(canned.clone());
+	
+	let info = client.key_info().await?;
+	assert_eq!(info.user_id, "m7n6j8");
+	
+	let res = client.levels(LevelSearch::new().limit(5)?).await;
+	assert!(matches!(res, Err(ClientError::Transport(ref err)) if err.status() == Some(404)));
+	
+	assert_eq!(canned.requested(), ["delegation/keys/@this", "levelhead/levels?limit=5"]);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn response_problems() -> Result<(), Error> {
+	let transport = Canned::default()
+		.with("delegation/keys/@this", r#"{"data": {"userId": "m7n6j8", "passId": "pass", "permissions": [], "new": 1}}"#)
+		.with("levelhead/aliases?userIds=nobody", r#"{"message": "Nope"}"#)
+		.with("levelhead/players?userIds=broken&includeAliases=true", "<html>");
+	let client = RumpusClient::new(transport).field_policy(FieldPolicy::Strict);
+	
+	let res = client.key_info().await;
+	assert!(matches!(res, Err(ClientError::UnknownFields(ref err)) if err.fields[0].path == "data.new"));
+	
+	let res = client.alias("nobody").await;
+	assert!(matches!(res, Err(ClientError::NoData { message: Some(ref m) }) if m == "Nope"));
+	
+	let res = client.player("broken").await;
+	assert!(matches!(res, Err(ClientError::Parse { ref body, .. }) if body == "<html>"));
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn caches() -> Result<(), Error> {
+	let canned = Canned::default()
+		.with("delegation/keys/@this", KEY_INFO)
+		.with("levelhead/aliases?userIds=nobody", "<html>");
+	let cache = ResponseCache::new();
+	let client = RumpusClient::new(canned.clone()).cache(cache.clone());
+	
+	client.key_info().await?;
+	client.key_info().await?;
+	assert_eq!(canned.calls(), 1);
+	
+	cache.invalidate(Endpoint::KeyInfo);
+	client.key_info().await?;
+	assert_eq!(canned.calls(), 2);
+	
+	//Broken responses don't get cached
+	assert!(client.alias("nobody").await.is_err());
+	assert_eq!(cache.len(), 1);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn shares_cache() -> Result<(), Error> {
+	const OTHER: &str = "other-mock-key";
+	let production = MockServer::start(common::dataset().key(OTHER, fixture::key_info("user01")).level(fixture::level(0))).await?;
+	let beta = MockServer::start(common::dataset().level(fixture::level(1))).await?;
+	let production_url = production.url();
+	let cache = ResponseCache::new();
+	let client = |key: &str, url: &str| -> Result<RumpusClient, Error> {
+		Ok(RumpusClient::with_key_url(key, url)?.cache(cache.clone()))
+	};
+	
+	//Keys don't get each other's key info
+	assert_eq!(client(KEY, &production_url)?.key_info().await?.user_id, "user00");
+	assert_eq!(client(OTHER, &production_url)?.key_info().await?.user_id, "user01");
+	//Environments don't get each other's levels
+	let levels = |levels: Vec<Level>| levels.into_iter().map(|level| level.level_id).collect::<Vec<_>>();
+	assert_eq!(levels(client(KEY, &production_url)?.levels(LevelSearch::new()).await?), ["code00"]);
+	assert_eq!(levels(client(KEY, &beta.url())?.levels(LevelSearch::new()).await?), ["code01"]);
+	assert_eq!(cache.len(), 4);
+	
+	//Clients with the same key and url do share responses
+	drop(production);
+	assert_eq!(client(OTHER, &production_url)?.key_info().await?.user_id, "user01");
+	
+	//Keys pinned from a pool don't get each other's key info either
+	let both = MockServer::start(common::dataset().key(OTHER, fixture::key_info("user01"))).await?;
+	let pool = KeyPool::new()
+		.key(DelegationKey::new(KEY), both.url())?
+		.key(DelegationKey::new(OTHER), both.url())?;
+	assert!(pool.validate().await.is_empty());
+	for user_id in ["user00", "user01"] {
+		let pinned = RumpusClient::new(pool.pinned(user_id).expect("key wasn't validated")).cache(cache.clone());
+		assert_eq!(pinned.key_info().await?.user_id, user_id);
+	}
+	Ok(())
+}
+
+async fn server() -> Result<MockServer, Error> {
+	let players = (0..10).map(|n| fixture::player_with(n, (n / 3) as Stat, 0));
+	Ok(MockServer::start(common::dataset().players(players)).await?)
+}
+
+#[tokio::test]
+async fn player_records() -> Result<(), Error> {
+	let levels = (0..5).map(|n| {
+		let records = LevelRecords {
+			high_score: vec![fixture::record("user01", 900.0), fixture::record("user02", 800.0)],
+			fastest_time: vec![fixture::record(if n == 3 { "user02" } else { "user01" }, 12.5)],
+			..Default::default()
+		};
+		Level { records: Some(records), ..fixture::level(n) }
+	});
+	let server = MockServer::start(common::dataset().levels(levels)).await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	//Small pages, to make sure every page gets collected
+	let held = client.player_records("user02", LevelSearch::new().limit(2)?).await?;
+	assert_eq!(held.user_id, "user02");
+	assert_eq!(held.records.len(), 6);
+	let world_records = held.world_records().map(|r| r.level_id.as_str()).collect::<Vec<_>>();
+	assert_eq!(world_records, ["code03"]);
+	assert_eq!(held.personal_bests().count(), 5);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn lookups() -> Result<(), Error> {
+	let server = server().await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	let found = client.player("user04").await?.expect("player should exist");
+	assert_eq!(found.alias.and_then(|a| a.alias).as_deref(), Some("Player 4"));
+	assert!(client.player("nobody").await?.is_none());
+	
+	assert_eq!(client.alias("user07").await?.and_then(|a| a.alias).as_deref(), Some("Player 7"));
+	assert!(client.level("nothing").await?.is_none());
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn pages() -> Result<(), Error> {
+	let server = server().await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	let search = PlayerSearch::new()
+		.sort(PlayerSortProperty::Subscribers, SortOrder::Ascending)
+		.limit(4)?;
+	let pages = client.pages(search).collect::<Vec<_>>().await;
+	let sizes = pages.iter().map(|p| p.as_ref().map(Vec::len).unwrap_or_default()).collect::<Vec<_>>();
+	assert_eq!(sizes, [4, 4, 2]);
+	
+	//Unsorted searches get sorted on creation date
+	let mut ids = Vec::new();
+	let mut pages = client.pages(PlayerSearch::new().limit(3)?);
+	while let Some(page) = pages.next().await {
+		ids.extend(page?.into_iter().map(|p| p.user_id));
+	}
+	assert_eq!(ids.len(), 10);
+	assert_eq!(ids[0], "user09");
+	
+	Ok(())
+}
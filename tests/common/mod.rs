@@ -0,0 +1,98 @@
+//!Helpers shared by the integration tests
+
+//Not every test uses every helper
+#![allow(dead_code)]
+
+use std::{
+	collections::HashMap,
+	path::Path,
+	sync::{Arc, Mutex},
+};
+
+use rumpus_ce::{
+	fixture,
+	mock::Dataset,
+	replay::read_fixture,
+	transport::{Transport, TransportError, TransportFuture},
+	types::*,
+};
+
+use anyhow::Error;
+
+pub const KEY: &str = "mock-key";
+
+///An empty dataset that accepts [KEY] as the key of `user00`
+pub fn dataset() -> Dataset {
+	Dataset::new().key(KEY, fixture::key_info("user00"))
+}
+
+///The data of the response in `tests/fixtures/{name}`
+pub fn fixture_data<T: serde::de::DeserializeOwned>(name: &str) -> Result<T, Error> {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+	let response = read_fixture::<Rumpus<T>>(&path)?;
+	Ok(response.data.expect("fixture has data"))
+}
+
+///The levels of the oldest first level fixture
+pub fn fixture_levels() -> Result<Vec<Level>, Error> {
+	fixture_data("levelhead_levels_sort_-createdAt_limit_64_includeStats_true_includeRecords_true_includeAliases_true_includeMyInteractions_true_includeBeta_true.json")
+}
+
+///The players of the oldest first player fixture
+pub fn fixture_players() -> Result<Vec<Player>, Error> {
+	fixture_data("levelhead_players_sort_-createdAt_limit_16_includeAliases_true_includeMyInteractions_true.json")
+}
+
+///Serves canned bodies and remembers what was requested, clones share what they remember
+#[derive(Clone, Default)]
+pub struct Canned {
+	bodies: HashMap<String, String>,
+	otherwise: Option<Result<String, u16>>,
+	requested: Arc<Mutex<Vec<String>>>,
+}
+
+impl Canned {
+	///Answers every path without a [body](Self::with) with `body`
+	pub fn answering(body: &str) -> Self {
+		Self {
+			otherwise: Some(Ok(body.into())),
+			..Self::default()
+		}
+	}
+	
+	///Answers every path without a [body](Self::with) with an HTTP error
+	pub fn failing(status: u16) -> Self {
+		Self {
+			otherwise: Some(Err(status)),
+			..Self::default()
+		}
+	}
+	
+	///Answers requests for `path` with `body`
+	pub fn with(mut self, path: &str, body: &str) -> Self {
+		self.bodies.insert(path.into(), body.into());
+		self
+	}
+	
+	///The paths requested so far
+	pub fn requested(&self) -> Vec<String> {
+		self.requested.lock().unwrap().clone()
+	}
+	
+	pub fn calls(&self) -> usize {
+		self.requested.lock().unwrap().len()
+	}
+}
+
+impl Transport for Canned {
+	///Paths without a body get a 404, unless told to answer something else
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		self.requested.lock().unwrap().push(path.to_owned());
+		let response = match (self.bodies.get(path), &self.otherwise) {
+			(Some(body), _) | (None, Some(Ok(body))) => Ok(body.clone()),
+			(None, Some(Err(status))) => Err(TransportError::Status { status: *status, body: String::new() }),
+			(None, None) => Err(TransportError::Status { status: 404, body: "Not Found".into() }),
+		};
+		Box::pin(async move { response })
+	}
+}
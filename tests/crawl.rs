@@ -0,0 +1,156 @@
+use std::{
+	collections::HashSet,
+	path::PathBuf,
+	sync::Mutex,
+	time::Duration,
+};
+
+use rumpus_ce::{
+	client::RumpusClient,
+	crawl::{read_archive, Checkpoint, Crawler},
+	fixture,
+	mock::{Dataset, MockServer},
+	query::*,
+	transport::{Transport, TransportError, TransportFuture},
+	types::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::KEY;
+
+fn dataset(levels: u32) -> Dataset {
+	//Pairs of levels created at the same time, to page through ties
+	common::dataset().levels((0..levels).map(|n| fixture::level_on(n, n / 2 + 1)))
+}
+
+///A fresh directory for a test
+fn directory(name: &str) -> Result<PathBuf, Error> {
+	let directory = std::env::temp_dir().join(format!("rumpus-crawl-{name}-{}", std::process::id()));
+	if directory.exists() {
+		std::fs::remove_dir_all(&directory)?;
+	}
+	Ok(directory)
+}
+
+fn crawler(client: RumpusClient, directory: &PathBuf) -> Result<Crawler<LevelSearch>, Error> {
+	Ok(Crawler::levels(client, directory)
+		.search(LevelSearch::new().include_stats(true).limit(3)?)
+		.interval(Duration::ZERO))
+}
+
+#[tokio::test]
+async fn resumes() -> Result<(), Error> {
+	let directory = directory("resumes")?;
+	
+	let server = MockServer::start(dataset(10)).await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	let progress = crawler(client.clone(), &directory)?.max_pages(2).run().await?;
+	assert_eq!(progress.archived, 6);
+	
+	let checkpoint = Checkpoint::load(directory.join("levels.checkpoint.json"))?;
+	assert_eq!(checkpoint.tiebreaker_item_id.as_deref(), Some("level05"));
+	
+	let progress = crawler(client, &directory)?.run().await?;
+	assert_eq!(progress.archived, 4);
+	assert_eq!(progress.total, 10);
+	
+	//New levels get picked up by the next run
+	drop(server);
+	let server = MockServer::start(dataset(13)).await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	let progress = crawler(client, &directory)?.run().await?;
+	assert_eq!(progress.archived, 3);
+	
+	let ids = read_archive::<Level>(directory.join("levels.ndjson"))?
+		.map(|level| level.map(|l| l.item.id))
+		.collect::<Result<Vec<_>, _>>()?;
+	assert_eq!(ids.len(), 13);
+	assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 13);
+	
+	std::fs::remove_dir_all(&directory)?;
+	Ok(())
+}
+
+///Rate limits every other request
+struct Limited<T> {
+	inner: T,
+	requests: Mutex<u32>,
+}
+
+impl<T: Transport> Transport for Limited<T> {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		let mut requests = self.requests.lock().unwrap();
+		*requests += 1;
+		if *requests % 2 == 1 {
+			Box::pin(async { Err(TransportError::Status { status: 429, body: "Too Many Requests".into() }) })
+		} else {
+			self.inner.get(path)
+		}
+	}
+}
+
+#[tokio::test]
+async fn retries_when_limited() -> Result<(), Error> {
+	let directory = directory("limited")?;
+	
+	let server = MockServer::start(dataset(5)).await?;
+	let transport = rumpus_ce::transport::RestsonTransport::with_key_url(KEY, server.url())?;
+	let client = RumpusClient::new(Limited { inner: transport, requests: Mutex::new(0) });
+	
+	let progress = crawler(client.clone(), &directory)?
+		.retries(1, Duration::from_millis(1))
+		.run().await?;
+	assert_eq!(progress.total, 5);
+	
+	let res = crawler(client, &directory)?.retries(0, Duration::ZERO).run().await;
+	assert!(res.is_err());
+	
+	std::fs::remove_dir_all(&directory)?;
+	Ok(())
+}
+
+fn player(n: u32) -> Player {
+	let mut player = Player {
+		stats: PlayerStats {
+			subscribers: n as Stat,
+			..Default::default()
+		},
+		..fixture::player(n)
+	};
+	if n == 3 {
+		//Deleted, like pg11x1
+		player.alias = Some(Alias {
+			alias_type: None,
+			alias: None,
+			anonymous: Some(true),
+			..fixture::alias(&player.user_id, n)
+		});
+	}
+	player
+}
+
+#[tokio::test]
+async fn players() -> Result<(), Error> {
+	let directory = directory("players")?;
+	
+	let server = MockServer::start(dataset(0).players((0..5).map(player))).await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	let crawler = Crawler::players(client, &directory).interval(Duration::ZERO);
+	assert_eq!(crawler.run().await?.total, 5);
+	
+	let players = read_archive::<Player>(crawler.archive_path())?.collect::<Result<Vec<_>, _>>()?;
+	let deleted = players.iter()
+		.filter(|p| p.item.appears_deleted())
+		.map(|p| p.item.user_id.as_str())
+		.collect::<Vec<_>>();
+	assert_eq!(deleted, ["user03"]);
+	assert_eq!(players[4].item.stats.subscribers, 4);
+	assert!(players[0].fetched_at().elapsed()? < Duration::from_secs(60));
+	
+	std::fs::remove_dir_all(&directory)?;
+	Ok(())
+}
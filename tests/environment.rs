@@ -0,0 +1,51 @@
+use rumpus_ce::{
+	client::RumpusClient,
+	environment::{Environment, Environments},
+	fixture,
+	mock::MockServer,
+	query::*,
+	types::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::KEY;
+
+fn level(n: u32, game_version: Option<&str>) -> Level {
+	Level {
+		game_version: game_version.map(Into::into),
+		stats: None,
+		..fixture::level(n)
+	}
+}
+
+async fn server(levels: impl IntoIterator<Item = Level>) -> Result<MockServer, Error> {
+	Ok(MockServer::start(common::dataset().levels(levels)).await?)
+}
+
+#[tokio::test]
+async fn diffs() -> Result<(), Error> {
+	let production = server([level(0, Some("1.0")), level(1, Some("1.0")), level(2, Some("1.0"))]).await?;
+	let beta = server([level(1, Some("1.0")), level(2, Some("1.1")), level(3, Some("1.1"))]).await?;
+	
+	let environments = Environments::new(
+		RumpusClient::with_key_environment(KEY, &production.url().parse()?)?,
+		RumpusClient::with_key_environment(KEY, &beta.url().parse::<Environment>()?)?,
+	);
+	let diff = environments.levels(LevelSearch::new().oldest_first()).await?;
+	
+	assert_eq!(diff.only_first.iter().map(|level| &level.level_id).collect::<Vec<_>>(), ["code00"]);
+	assert_eq!(diff.only_second.iter().map(|level| &level.level_id).collect::<Vec<_>>(), ["code03"]);
+	assert_eq!(diff.unchanged, 1);
+	let [changed] = diff.changed.as_slice() else {
+		panic!("expected 1 changed level: {:?}", diff.changed);
+	};
+	assert_eq!(changed.first.level_id, "code02");
+	assert_eq!(changed.fields, ["gameVersion"]);
+	assert!(!diff.is_empty());
+	
+	let same = Environments::new(environments.first.clone(), environments.first).players(PlayerSearch::new()).await?;
+	assert!(same.is_empty());
+	Ok(())
+}
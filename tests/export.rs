@@ -0,0 +1,64 @@
+use futures_util::stream;
+use rumpus_ce::export::Exporter;
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::{fixture_levels, fixture_players};
+
+#[test]
+fn csv() -> Result<(), Error> {
+	let levels = fixture_levels()?;
+	let mut exporter = Exporter::csv(Vec::new());
+	exporter.write_all(&levels)?;
+	assert_eq!(exporter.written(), levels.len());
+	let csv = String::from_utf8(exporter.finish()?)?;
+	
+	let mut lines = csv.lines();
+	let header = lines.next().unwrap_or_default();
+	assert!(header.starts_with("_id,levelId,title,userId,alias.alias,"));
+	assert!(header.contains(",content.World,"));
+	assert!(header.contains(",stats.Likes,"));
+	assert!(header.ends_with(",interactions.favorited"));
+	assert!(lines.next().unwrap_or_default().contains(&levels[0].level_id));
+	
+	//Levels without stats keep the same columns
+	let mut stripped = levels[0].clone();
+	stripped.stats = None;
+	let mut exporter = Exporter::csv(Vec::new());
+	exporter.write(&stripped)?;
+	let stripped_csv = String::from_utf8(exporter.finish()?)?;
+	assert_eq!(stripped_csv.lines().next(), Some(header));
+	
+	Ok(())
+}
+
+#[test]
+fn ndjson() -> Result<(), Error> {
+	let players = fixture_players()?;
+	let mut exporter = Exporter::ndjson(Vec::new());
+	let written = exporter.write_pages_blocking([Ok(players[..4].to_vec()), Ok(players[4..].to_vec())])?;
+	assert_eq!(written, players.len());
+	let ndjson = String::from_utf8(exporter.finish()?)?;
+	
+	let rows = ndjson.lines()
+		.map(serde_json::from_str::<serde_json::Value>)
+		.collect::<Result<Vec<_>, _>>()?;
+	assert_eq!(rows.len(), players.len());
+	assert_eq!(rows[0]["userId"], players[0].user_id.as_str());
+	assert_eq!(rows[0]["stats.Subscribers"], players[0].stats.subscribers);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn pages() -> Result<(), Error> {
+	let levels = fixture_levels()?;
+	let pages = stream::iter([Ok(levels.clone()), Ok(levels.clone())]);
+	let mut exporter = Exporter::csv(Vec::new());
+	assert_eq!(exporter.write_pages(pages).await?, levels.len() * 2);
+	let csv = String::from_utf8(exporter.finish()?)?;
+	assert_eq!(csv.lines().count(), levels.len() * 2 + 1);
+	
+	Ok(())
+}
@@ -0,0 +1,100 @@
+/*!
+Makes sure every response in `tests/fixtures` can be parsed.
+
+The fixtures are hand-written rather than recorded (see the README there),
+so this checks the crate against the responses it expects, not against what the real API returns.
+
+Run this both with and without the `undocumented` feature.
+*/
+
+use std::{fs, path::Path};
+
+use rumpus_ce::{
+	query::*,
+	replay::{read_fixture, ReplayClient, ReplayError},
+	types::*,
+	unknown::FieldPolicy,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+#[cfg(feature="undocumented")]
+use common::fixture_data;
+
+///Parses the fixture as the type the endpoint it was recorded from returns, and applies the policy to it
+fn parse(path: &Path, policy: &FieldPolicy) -> Result<(), Error> {
+	let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+	if name.starts_with("delegation_keys_@this") {
+		policy.check(&read_fixture::<Rumpus<DelegationKeyInfo>>(path)?)?;
+	} else if name.starts_with("levelhead_players") {
+		policy.check(&read_fixture::<Rumpus<Vec<Player>>>(path)?)?;
+	} else if name.starts_with("levelhead_levels") {
+		policy.check(&read_fixture::<Rumpus<Vec<Level>>>(path)?)?;
+	} else if name.starts_with("levelhead_aliases") {
+		policy.check(&read_fixture::<Rumpus<Vec<Alias>>>(path)?)?;
+	} else {
+		panic!("don't know which endpoint fixture {name} is from");
+	}
+	Ok(())
+}
+
+fn parse_all(policy: &FieldPolicy) -> Result<(), Error> {
+	let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+	let mut count = 0;
+	
+	for entry in fs::read_dir(dir)? {
+		let path = entry?.path();
+		if path.extension().is_some_and(|e| e == "json") {
+			parse(&path, policy)?;
+			count += 1;
+		}
+	}
+	
+	assert!(count > 0, "no fixtures were found");
+	
+	Ok(())
+}
+
+#[test]
+fn all_fixtures_parse() -> Result<(), Error> {
+	parse_all(&FieldPolicy::Lenient)
+}
+
+///Only the undocumented fields are unknown without the undocumented feature.
+///The fixtures only contain fields that were known when they were written, so this can't notice new fields of the real API.
+#[cfg(feature="undocumented")]
+#[test]
+fn no_unknown_fields() -> Result<(), Error> {
+	parse_all(&FieldPolicy::Strict)
+}
+
+///The undocumented fields are in the fixtures, make sure they get picked up
+#[cfg(feature="undocumented")]
+#[test]
+fn undocumented_fields() -> Result<(), Error> {
+	let name = "levelhead_levels_sort_createdAt_limit_64_includeStats_true_includeRecords_true_includeAliases_true_includeMyInteractions_true_includeBeta_true.json";
+	let levels = fixture_data::<Vec<Level>>(name)?;
+	
+	assert!(levels.iter().filter_map(|l| l.stats.as_ref()).any(|s| s.hidden_gem > 0));
+	
+	Ok(())
+}
+
+///Replaying finds the fixture of a request by its name
+#[tokio::test]
+async fn replays() -> Result<(), Error> {
+	let client = ReplayClient::replay(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures"));
+	let search = PlayerSearch::new()
+		.newest_first()
+		.limit(16)?
+		.include_aliases(true)
+		.include_my_interactions(true);
+	let players = client.get::<_, Rumpus<Vec<Player>>>(search).await?.data.expect("fixture has data");
+	assert_eq!(players.len(), 16);
+	
+	let missing = client.get::<_, Rumpus<Vec<Player>>>(PlayerSearch::new().limit(3)?).await;
+	assert!(matches!(missing, Err(ReplayError::Io { .. })));
+	
+	Ok(())
+}
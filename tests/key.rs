@@ -0,0 +1,73 @@
+use std::{env, fs};
+
+use rumpus_ce::{
+	key::{KeyError, KeySource},
+	mock::{Dataset, MockServer},
+	types::*,
+};
+
+use anyhow::{Error, Result};
+
+const KEY: &str = "mock-key-0123456789";
+
+#[test]
+fn loads() -> Result<(), Error> {
+	let dir = env::temp_dir().join(format!("rumpus_ce-key-{}", std::process::id()));
+	fs::create_dir_all(&dir)?;
+	fs::write(dir.join(KeySource::FILE_NAME), format!("{KEY}\n"))?;
+	fs::write(dir.join("empty"), "\n")?;
+	
+	assert_eq!(KeySource::ConfigDir(dir.clone()).load()?.expose(), KEY);
+	assert!(matches!(KeySource::file(dir.join("empty")).load(), Err(KeyError::Empty { .. })));
+	assert!(matches!(KeySource::file(dir.join("missing")).load(), Err(KeyError::Missing { .. })));
+	
+	//Falls through sources that don't exist, but not ones that are broken
+	let var = "RUMPUS_CE_TEST_KEY_THAT_IS_NOT_SET";
+	let source = KeySource::FirstOf(vec![KeySource::Env(var.into()), KeySource::file(dir.join("missing")), KeySource::ConfigDir(dir.clone())]);
+	assert_eq!(source.load()?.expose(), KEY);
+	let source = KeySource::FirstOf(vec![KeySource::file(dir.join("empty")), KeySource::ConfigDir(dir.clone())]);
+	assert!(matches!(source.load(), Err(KeyError::Empty { .. })));
+	
+	let source = KeySource::FirstOf(vec![KeySource::Env(var.into()), KeySource::file(dir.join("missing"))]);
+	let Err(KeyError::Missing { tried }) = source.load() else {
+		panic!("found a key that doesn't exist");
+	};
+	assert!(tried.starts_with(&format!("environment variable {var}, ")));
+	
+	//An environment variable that isn't unicode is broken, not missing
+	#[cfg(unix)]
+	{
+		use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+		let var = "RUMPUS_CE_TEST_KEY_THAT_IS_NOT_UNICODE";
+		env::set_var(var, OsStr::from_bytes(b"key\xff"));
+		let source = KeySource::FirstOf(vec![KeySource::Env(var.into()), KeySource::ConfigDir(dir.clone())]);
+		assert!(matches!(source.load(), Err(KeyError::NotUnicode { .. })));
+	}
+	
+	fs::remove_dir_all(dir)?;
+	Ok(())
+}
+
+#[tokio::test]
+async fn validates() -> Result<(), Error> {
+	let dataset = Dataset::new()
+		.key(KEY, DelegationKeyInfo {
+			user_id: "user00".into(),
+			pass_id: "pass".into(),
+			permissions: [Permission::new("some-permission")].into(),
+			extra: Extra::default(),
+		});
+	let server = MockServer::start(dataset).await?;
+	
+	let validated = KeySource::key(KEY).load_validated(server.url()).await?;
+	assert_eq!(validated.info.user_id, "user00");
+	assert_eq!(validated.to_string(), "delegation key ****6789 of user00, allowed: some-permission");
+	
+	let rejected = KeySource::key("not-a-real-key-xyz").load_validated(server.url()).await;
+	let Err(err @ KeyError::Rejected { status: 401, .. }) = rejected else {
+		panic!("wasn't rejected: {rejected:?}");
+	};
+	assert!(!err.to_string().contains("not-a-real-key"));
+	
+	Ok(())
+}
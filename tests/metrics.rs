@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use metrics_util::{
+	CompositeKey, MetricKind,
+	debugging::{DebugValue, DebuggingRecorder, Snapshotter},
+};
+use rumpus_ce::{
+	cache::ResponseCache,
+	client::RumpusClient,
+	fixture,
+	key::DelegationKey,
+	metrics::*,
+	mock::{Dataset, MockServer},
+	pool::KeyPool,
+	query::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::Canned;
+
+const KEY: &str = "mock-key-0123456789";
+
+type Snapshot = Vec<(CompositeKey, DebugValue)>;
+
+fn snapshot(snapshotter: &Snapshotter) -> Snapshot {
+	snapshotter.snapshot().into_vec().into_iter().map(|(key, _, _, value)| (key, value)).collect()
+}
+
+fn value<'s>(snapshot: &'s Snapshot, kind: MetricKind, name: &str, labels: &[(&str, &str)]) -> Option<&'s DebugValue> {
+	snapshot.iter()
+		.find(|(key, _)| {
+			key.kind() == kind
+				&& key.key().name() == name
+				&& labels.iter().all(|(label, value)| key.key().labels().any(|l| l.key() == *label && l.value() == *value))
+		})
+		.map(|(_, value)| value)
+}
+
+fn counter(snapshot: &Snapshot, name: &str, labels: &[(&str, &str)]) -> u64 {
+	match value(snapshot, MetricKind::Counter, name, labels) {
+		Some(DebugValue::Counter(count)) => *count,
+		_ => 0,
+	}
+}
+
+///Histograms are emptied by every snapshot
+fn histogram(snapshot: &Snapshot, name: &str, labels: &[(&str, &str)]) -> usize {
+	match value(snapshot, MetricKind::Histogram, name, labels) {
+		Some(DebugValue::Histogram(values)) => values.len(),
+		_ => 0,
+	}
+}
+
+#[tokio::test]
+async fn records() -> Result<(), Error> {
+	//Installing a recorder is global, so this is the only test in this file
+	let recorder = DebuggingRecorder::new();
+	let snapshotter = recorder.snapshotter();
+	recorder.install()?;
+	describe();
+	
+	let dataset = Dataset::new()
+		.key(KEY, fixture::key_info("user00"));
+	let server = MockServer::start(dataset).await?;
+	let pool = KeyPool::new()
+		.budget(2, Duration::from_millis(100))?
+		.key(DelegationKey::new("revoked-key-000000000"), server.url())?
+		.key(DelegationKey::new(KEY), server.url())?;
+	let client = RumpusClient::new(pool).cache(ResponseCache::new());
+	
+	client.key_info().await?;
+	client.key_info().await?;
+	//The good key only has 1 request left, so the second search has to wait
+	client.levels(LevelSearch::new().limit(5)?).await?;
+	client.levels(LevelSearch::new().limit(6)?).await?;
+	
+	let metrics = snapshot(&snapshotter);
+	//The revoked key got a request too, before the pool retried it with the good key
+	assert_eq!(counter(&metrics, REQUESTS, &[("endpoint", "key_info")]), 2);
+	assert_eq!(histogram(&metrics, REQUEST_DURATION, &[("endpoint", "key_info")]), 2);
+	assert_eq!(counter(&metrics, REQUESTS, &[("endpoint", "levels")]), 2);
+	assert_eq!(histogram(&metrics, REQUEST_DURATION, &[("endpoint", "levels")]), 2);
+	assert!(counter(&metrics, RECEIVED_BYTES, &[("endpoint", "key_info")]) > 0);
+	assert_eq!(counter(&metrics, CACHE_HITS, &[("endpoint", "key_info")]), 1);
+	assert_eq!(counter(&metrics, CACHE_MISSES, &[("endpoint", "key_info")]), 1);
+	assert_eq!(counter(&metrics, CACHE_MISSES, &[("endpoint", "levels")]), 2);
+	assert_eq!(counter(&metrics, RATE_LIMIT_WAITS, &[("source", "pool")]), 1);
+	assert_eq!(histogram(&metrics, RATE_LIMIT_WAIT, &[("source", "pool")]), 1);
+	//The pool handled the revoked key, so the request didn't fail
+	assert_eq!(counter(&metrics, ERRORS, &[]), 0);
+	
+	let garbage = RumpusClient::new(Canned::answering("<html>"));
+	assert!(garbage.key_info().await.is_err());
+	let metrics = snapshot(&snapshotter);
+	assert_eq!(counter(&metrics, ERRORS, &[("endpoint", "key_info"), ("kind", "parse")]), 1);
+	assert_eq!(counter(&metrics, REQUESTS, &[("endpoint", "key_info")]), 3);
+	
+	let single = RumpusClient::new(KeyPool::new().key(DelegationKey::new("revoked-key-000000000"), server.url())?);
+	assert!(single.key_info().await.is_err());
+	let metrics = snapshot(&snapshotter);
+	assert_eq!(counter(&metrics, ERRORS, &[("endpoint", "key_info"), ("kind", "rejected")]), 1);
+	//Only the revoked key was sent, running out of keys afterwards isn't another request
+	assert_eq!(counter(&metrics, REQUESTS, &[("endpoint", "key_info")]), 4);
+	Ok(())
+}
@@ -0,0 +1,193 @@
+use std::collections::HashSet;
+
+use rumpus_ce::{
+	fixture,
+	mock::{Dataset, MockServer},
+	types::*,
+	query::*,
+};
+
+use anyhow::{Error, Result};
+
+const KEY: &str = "mock-key";
+
+fn player(n: u32, subscribers: Stat) -> Player {
+	Player {
+		interactions: Some(PlayerInteractions { following: false, extra: Extra::default() }),
+		stats: PlayerStats {
+			subscribers,
+			..Default::default()
+		},
+		..fixture::player(n)
+	}
+}
+
+fn level(n: u32, creator: u32, play_time: Stat) -> Level {
+	Level {
+		user_id: format!("user{creator:02}"),
+		tower: Some(n % 4 != 0),
+		tags: if n % 2 == 0 { vec!["ltag_short".into()] } else { vec![] },
+		stats: Some(LevelStats {
+			play_time,
+			diamonds: (n % 6) as u8,
+			..Default::default()
+		}),
+		..fixture::level(n)
+	}
+}
+
+async fn server() -> Result<MockServer, Error> {
+	let dataset = Dataset::new()
+		.key(KEY, DelegationKeyInfo {
+			permissions: HashSet::from([Permission::new("some-permission")]),
+			..fixture::key_info("user00")
+		})
+		//Lots of ties to test paging
+		.players((0..10).map(|n| player(n, (n / 3) as Stat)))
+		.levels((0..20).map(|n| level(n, n % 3, (n % 5) as Stat * 100)));
+	Ok(MockServer::start(dataset).await?)
+}
+
+async fn players(server: &MockServer, search: PlayerSearch) -> Result<Vec<Player>, Error> {
+	let res = server.client(KEY).get::<_, Rumpus<Vec<Player>>>(search).await?;
+	Ok(res.into_inner().data.expect("no data was returned"))
+}
+
+async fn levels(server: &MockServer, search: LevelSearch) -> Result<Vec<Level>, Error> {
+	let res = server.client(KEY).get::<_, Rumpus<Vec<Level>>>(search).await?;
+	Ok(res.into_inner().data.expect("no data was returned"))
+}
+
+#[tokio::test]
+async fn key_info() -> Result<(), Error> {
+	let server = server().await?;
+	let res = server.client(KEY).get::<_, Rumpus<DelegationKeyInfo>>(()).await?;
+	let info = res.into_inner().data.expect("no data was returned");
+	
+	assert_eq!(info.user_id, "user00");
+	assert!(info.can("some-permission"));
+	assert!(!info.can("other-permission"));
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn wrong_key() -> Result<(), Error> {
+	let server = server().await?;
+	let res = server.client("not-a-key").get::<_, Rumpus<DelegationKeyInfo>>(()).await;
+	
+	assert!(matches!(res, Err(restson::Error::HttpError(401, _))));
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn player_filters() -> Result<(), Error> {
+	let server = server().await?;
+	
+	let data = players(&server, PlayerSearch::new().oldest_first().limit(3)?).await?;
+	let ids = data.iter().map(|p| p.user_id.as_str()).collect::<Vec<_>>();
+	assert_eq!(ids, ["user00", "user01", "user02"]);
+	//Not requested
+	assert!(data[0].alias.is_none());
+	assert!(data[0].interactions.is_none());
+	
+	let search = PlayerSearch::new()
+		.user_ids(vec!["user01", "user05", "user09"])?
+		.min_subscribers(1)
+		.include_aliases(true);
+	let data = players(&server, search).await?;
+	assert_eq!(data.len(), 2);
+	assert!(data.iter().all(|p| p.alias.is_some()));
+	
+	let data = players(&server, PlayerSearch::new().max_created_at("2020-01-03T00:00:00.000Z")).await?;
+	assert_eq!(data.len(), 3);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn page_players_through_ties() -> Result<(), Error> {
+	let server = server().await?;
+	
+	let mut search = PlayerSearch::new()
+		.sort(PlayerSortProperty::Subscribers, SortOrder::Descending)
+		.limit(2)?;
+	let mut seen = Vec::new();
+	loop {
+		let page = players(&server, search.clone()).await?;
+		let Some(last) = page.last() else {
+			break;
+		};
+		search = search.page_after(last)?;
+		seen.extend(page.into_iter().map(|p| p.stats.subscribers));
+	}
+	
+	assert_eq!(seen, [3, 2, 2, 2, 1, 1, 1, 0, 0, 0]);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn level_filters() -> Result<(), Error> {
+	let server = server().await?;
+	
+	//Tower only by default
+	let data = levels(&server, LevelSearch::new()).await?;
+	assert_eq!(data.len(), 15);
+	assert!(data.iter().all(|l| l.stats.is_none()));
+	
+	let search = LevelSearch::new()
+		.user_ids(vec!["user01"])?
+		.include_stats(true);
+	let data = levels(&server, search).await?;
+	assert_eq!(data.len(), 7);
+	assert!(data.iter().all(|l| l.stats.is_some()));
+	
+	let data = levels(&server, LevelSearch::new().tags("ltag_short").diamonds(2u8)).await?;
+	let ids = data.iter().map(|l| l.level_id.as_str()).collect::<HashSet<_>>();
+	assert_eq!(ids, HashSet::from(["code02", "code14"]));
+	
+	let data = levels(&server, LevelSearch::new().marketing(true)).await?;
+	assert_eq!(data.len(), 5);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn page_levels_through_ties() -> Result<(), Error> {
+	let server = server().await?;
+	
+	let mut search = LevelSearch::new()
+		.sort(LevelSortProperty::PlayTime, SortOrder::Ascending)
+		.include_stats(true)
+		.limit(3)?;
+	let mut seen = Vec::new();
+	loop {
+		let page = levels(&server, search.clone()).await?;
+		let Some(last) = page.last() else {
+			break;
+		};
+		search = search.page_after(last)?;
+		seen.extend(page.into_iter().map(|l| l.id));
+	}
+	
+	assert_eq!(seen.len(), 15);
+	assert_eq!(seen.iter().collect::<HashSet<_>>().len(), 15);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn aliases() -> Result<(), Error> {
+	let server = server().await?;
+	
+	let search = AliasSearch::new().user_ids(vec!["user03", "deleted"])?;
+	let res = server.client(KEY).get::<_, Rumpus<Vec<Alias>>>(search).await?;
+	let data = res.into_inner().data.expect("no data was returned");
+	
+	assert_eq!(data[0].alias.as_deref(), Some("Player 3"));
+	assert_eq!(data[1].anonymous, Some(true));
+	
+	Ok(())
+}
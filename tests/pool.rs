@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+use rumpus_ce::{
+	client::RumpusClient,
+	key::DelegationKey,
+	mock::{Dataset, MockServer},
+	pool::{KeyPool, PoolError},
+	transport::{Transport, TransportError},
+	types::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::Canned;
+
+const KEYS: [&str; 3] = ["mock-key-aaaaaaaaaaaa", "mock-key-bbbbbbbbbbbb", "mock-key-cccccccccccc"];
+
+fn info(user_id: &str) -> DelegationKeyInfo {
+	DelegationKeyInfo {
+		user_id: user_id.into(),
+		pass_id: "pass".into(),
+		permissions: [Permission::new("some-permission")].into(),
+		extra: Extra::default(),
+	}
+}
+
+async fn mock_pool(keys: &[&str]) -> Result<(MockServer, KeyPool), Error> {
+	let dataset = Dataset::new()
+		.key(KEYS[0], info("user00"))
+		.key(KEYS[1], info("user01"))
+		.key(KEYS[2], info("user02"));
+	let server = MockServer::start(dataset).await?;
+	let mut pool = KeyPool::new();
+	for key in keys {
+		pool = pool.key(DelegationKey::new(*key), server.url())?;
+	}
+	Ok((server, pool))
+}
+
+#[tokio::test]
+async fn spreads_and_pins() -> Result<(), Error> {
+	let (_server, pool) = mock_pool(&KEYS).await?;
+	let pool = pool.budget(10, Duration::from_secs(60))?;
+	assert!(pool.validate().await.is_empty());
+	let owners = pool.status().into_iter().map(|status| status.owner.unwrap_or_default()).collect::<Vec<_>>();
+	assert_eq!(owners, ["user00", "user01", "user02"]);
+	
+	//Every key was used once to validate it, so the next requests go round
+	let client = RumpusClient::new(pool.clone());
+	for _ in 0..6 {
+		client.key_info().await?;
+	}
+	assert!(pool.status().iter().all(|status| status.remaining == 7));
+	
+	let pinned = RumpusClient::new(pool.pinned("user01").expect("no key for user01"));
+	assert_eq!(pinned.key_info().await?.user_id, "user01");
+	assert_eq!(pool.status()[1].remaining, 6);
+	//The key with the most budget left goes first
+	client.key_info().await?;
+	client.key_info().await?;
+	assert!(pool.status().iter().all(|status| status.remaining == 6));
+	
+	assert!(pool.pinned("user99").is_none());
+	Ok(())
+}
+
+#[tokio::test]
+async fn fails_over() -> Result<(), Error> {
+	let (_server, pool) = mock_pool(&["revoked-key-000000000", KEYS[0]]).await?;
+	let client = RumpusClient::new(pool.clone());
+	
+	assert_eq!(client.key_info().await?.user_id, "user00");
+	let status = pool.status();
+	assert!(status[0].revoked);
+	assert!(!status[1].revoked);
+	assert!(pool.pinned("user00").is_none(), "pinned before validating");
+	
+	let (_server, pool) = mock_pool(&["revoked-key-000000000"]).await?;
+	let result = RumpusClient::new(pool).key_info().await;
+	assert!(result.is_err_and(|err| err.to_string().contains("revoked")));
+	Ok(())
+}
+
+#[tokio::test]
+async fn waits_for_budget() -> Result<(), Error> {
+	let window = Duration::from_millis(200);
+	//No key could ever send a request
+	assert!(matches!(KeyPool::new().budget(0, window), Err(PoolError::ZeroBudget)));
+	let (first, second) = (Canned::answering("{}"), Canned::answering("{}"));
+	let pool = KeyPool::new()
+		.budget(1, window)?
+		.transport(first.clone())
+		.transport(second.clone());
+	
+	let start = Instant::now();
+	for _ in 0..4 {
+		pool.get("path").await?;
+	}
+	assert!(start.elapsed() >= window);
+	assert_eq!(first.calls(), 2);
+	assert_eq!(second.calls(), 2);
+	Ok(())
+}
+
+#[tokio::test]
+async fn rate_limited() -> Result<(), Error> {
+	let limited = Canned::failing(429);
+	let fine = Canned::answering("{}");
+	let pool = KeyPool::new()
+		.transport(limited.clone())
+		.transport(fine.clone());
+	
+	//Retried with the other key, after which the limited key isn't tried again
+	pool.get("path").await?;
+	pool.get("path").await?;
+	assert_eq!(limited.calls(), 1);
+	assert_eq!(fine.calls(), 2);
+	assert_eq!(pool.status()[0].remaining, 0);
+	
+	//Only passed on when every key is limited
+	let pool = KeyPool::new().transport(limited.clone());
+	let result = pool.get("path").await;
+	assert!(result.is_err_and(|err| err.status() == Some(429)));
+	Ok(())
+}
+
+#[tokio::test]
+async fn all_revoked() -> Result<(), Error> {
+	let (revoked, also_revoked) = (Canned::failing(401), Canned::failing(403));
+	let pool = KeyPool::new()
+		.transport(revoked.clone())
+		.transport(also_revoked.clone());
+	
+	//The rejections aren't passed on, running out of keys is
+	let result = pool.get("path").await;
+	let Err(TransportError::Other(err)) = result else {
+		panic!("expected the pool to run out of keys, got {result:?}");
+	};
+	assert!(matches!(err.downcast_ref(), Some(PoolError::AllRevoked)));
+	assert_eq!((revoked.calls(), also_revoked.calls()), (1, 1));
+	Ok(())
+}
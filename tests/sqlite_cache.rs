@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use futures_util::StreamExt;
+
+use rumpus_ce::{
+	client::RumpusClient,
+	fixture,
+	mock::MockServer,
+	query::*,
+	sqlite_cache::SqliteCache,
+	types::*,
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::KEY;
+
+async fn server() -> Result<MockServer, Error> {
+	let levels = (0..6).map(|n| Level {
+		user_id: format!("user{:02}", n % 2),
+		..fixture::level(n)
+	});
+	let dataset = common::dataset().levels(levels).players((0..5).map(fixture::player));
+	Ok(MockServer::start(dataset).await?)
+}
+
+#[tokio::test]
+async fn stores_fetched() -> Result<(), Error> {
+	let server = server().await?;
+	let cache = SqliteCache::open_in_memory()?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?.sqlite_cache(cache.clone());
+	
+	client.levels(LevelSearch::new().user_ids(vec!["user01"])?).await?;
+	assert_eq!(cache.levels()?.len(), 3);
+	
+	let by_creator = cache.levels_by_creator("user01")?;
+	let titles = by_creator.iter().map(|l| l.value.title.as_str()).collect::<Vec<_>>();
+	assert_eq!(titles, ["Level 5", "Level 3", "Level 1"]);
+	
+	assert_eq!(cache.levels_titled("level 3")?.len(), 1);
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn stores_pages() -> Result<(), Error> {
+	let server = server().await?;
+	let cache = SqliteCache::open_in_memory()?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?.sqlite_cache(cache.clone());
+	
+	let pages = client.pages(LevelSearch::new().limit(4)?).collect::<Vec<_>>().await;
+	assert_eq!(pages.len(), 2);
+	let pages = client.pages(PlayerSearch::new().limit(2)?).collect::<Vec<_>>().await;
+	assert_eq!(pages.len(), 3);
+	
+	assert_eq!(cache.levels()?.len(), 6);
+	assert_eq!(cache.level("code04")?.map(|l| l.value.title).as_deref(), Some("Level 4"));
+	assert_eq!(cache.players()?.len(), 5);
+	assert!(cache.player("user03")?.is_some());
+	
+	Ok(())
+}
+
+#[tokio::test]
+async fn serves_lookups() -> Result<(), Error> {
+	let server = server().await?;
+	let cache = SqliteCache::open_in_memory()?;
+	let url = server.url();
+	
+	let client = RumpusClient::with_key_url(KEY, &url)?.sqlite_cache(cache.clone());
+	assert!(client.level("code03").await?.is_some());
+	
+	//Nothing can be fetched anymore, so it has to come from the cache
+	drop(server);
+	assert_eq!(client.level("code03").await?.map(|l| l.title).as_deref(), Some("Level 3"));
+	
+	//Stale levels are still served
+	let client = RumpusClient::with_key_url(KEY, &url)?.sqlite_cache(cache.clone().fresh_for(Duration::ZERO));
+	assert!(client.level("code03").await?.is_some());
+	
+	//Expired ones are not
+	let client = RumpusClient::with_key_url(KEY, &url)?.sqlite_cache(cache.fresh_for(Duration::ZERO).stale_for(Duration::ZERO));
+	assert!(client.level("code03").await.is_err());
+	
+	Ok(())
+}
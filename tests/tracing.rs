@@ -0,0 +1,128 @@
+use std::{
+	io,
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use rumpus_ce::{
+	cache::ResponseCache,
+	client::RumpusClient,
+	crawl::Crawler,
+	fixture,
+	key::DelegationKey,
+	mock::{Dataset, MockServer},
+	pool::KeyPool,
+	query::*,
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::Canned;
+
+const KEY: &str = "mock-key-0123456789";
+const REVOKED: &str = "revoked-key-000000000";
+
+///Collects everything the subscriber writes
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+	fn text(&self) -> String {
+		String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+	}
+}
+
+impl io::Write for Captured {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		self.0.lock().unwrap().extend_from_slice(buf);
+		Ok(buf.len())
+	}
+	
+	fn flush(&mut self) -> io::Result<()> {
+		Ok(())
+	}
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+	type Writer = Self;
+	
+	fn make_writer(&'a self) -> Self {
+		self.clone()
+	}
+}
+
+#[tokio::test]
+async fn spans() -> Result<(), Error> {
+	let captured = Captured::default();
+	let subscriber = tracing_subscriber::fmt()
+		.with_max_level(tracing::Level::DEBUG)
+		.with_ansi(false)
+		.with_writer(captured.clone())
+		.finish();
+	let _guard = tracing::subscriber::set_default(subscriber);
+	
+	let dataset = Dataset::new()
+		.key(KEY, fixture::key_info("user00"));
+	let server = MockServer::start(dataset).await?;
+	let pool = KeyPool::new()
+		.key(DelegationKey::new(REVOKED), server.url())?
+		.key(DelegationKey::new(KEY), server.url())?;
+	let client = RumpusClient::new(pool).cache(ResponseCache::new());
+	
+	client.key_info().await?;
+	client.key_info().await?;
+	client.levels(LevelSearch::new().limit(5)?).await?;
+	let single = RumpusClient::new(KeyPool::new().key(DelegationKey::new(REVOKED), server.url())?);
+	assert!(single.key_info().await.is_err());
+	
+	let text = captured.text();
+	assert!(text.contains("delegation key revoked"), "{text}");
+	assert!(text.contains("endpoint=\"delegation/keys/@this\""), "{text}");
+	assert!(text.contains("endpoint=\"levelhead/levels\" query=\"limit=5\""), "{text}");
+	assert!(text.contains("cache=\"miss\""), "{text}");
+	assert!(text.contains("cache=\"hit\""), "{text}");
+	assert!(text.contains("status=401"), "{text}");
+	assert!(text.contains("status=200"), "{text}");
+	assert!(text.contains("key=****6789"), "{text}");
+	assert!(text.contains("retries=1"), "{text}");
+	assert!(text.contains("latency_ms="), "{text}");
+	//The pool ran out of keys, the span still has the status of the last response it got
+	assert!(text.lines().any(|line| line.contains("status=401") && line.contains("request failed")), "{text}");
+	//The second request was served from the cache, so it never reached the transport
+	assert_eq!(text.matches("request succeeded").count(), 2, "{text}");
+	assert_eq!(text.matches("served from the cache").count(), 1, "{text}");
+	
+	assert!(!text.contains("mock-key-0123"), "leaked the key: {text}");
+	assert!(!text.contains("revoked-key-0"), "leaked the key: {text}");
+	Ok(())
+}
+
+#[tokio::test]
+async fn crawl_retries() -> Result<(), Error> {
+	let captured = Captured::default();
+	let subscriber = tracing_subscriber::fmt()
+		.with_max_level(tracing::Level::DEBUG)
+		.with_ansi(false)
+		.with_writer(captured.clone())
+		.finish();
+	let _guard = tracing::subscriber::set_default(subscriber);
+	
+	let directory = std::env::temp_dir().join(format!("rumpus-tracing-crawl-{}", std::process::id()));
+	let crawler = Crawler::levels(RumpusClient::new(Canned::failing(429)), &directory)
+		.retries(2, Duration::from_millis(1));
+	assert!(crawler.run().await.is_err());
+	
+	let text = captured.text();
+	let failed = text.lines().filter(|line| line.contains("request failed")).collect::<Vec<_>>();
+	assert_eq!(failed.len(), 3, "{text}");
+	assert!(!failed[0].contains("retry="), "{text}");
+	assert!(failed[1].contains("retry=1"), "{text}");
+	assert!(failed[2].contains("retry=2"), "{text}");
+	
+	if directory.exists() {
+		std::fs::remove_dir_all(&directory)?;
+	}
+	Ok(())
+}
@@ -0,0 +1,195 @@
+use std::{
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use futures_util::StreamExt;
+use rumpus_ce::{
+	client::RumpusClient,
+	fixture,
+	mock::MockServer,
+	query::*,
+	transport::{RestsonTransport, Transport, TransportFuture},
+	types::*,
+	watch::{LevelWatcher, StatEvent, StatWatcher, WatchCursor},
+};
+
+use anyhow::{Error, Result};
+
+mod common;
+use common::KEY;
+
+///Sends requests to whichever server is current, so levels can be "published" between polls
+#[derive(Clone, Default)]
+struct Switchable(Arc<Mutex<Option<Arc<RestsonTransport>>>>);
+
+impl Switchable {
+	fn switch(&self, server: &MockServer) -> Result<(), Error> {
+		*self.0.lock().unwrap() = Some(Arc::new(RestsonTransport::with_key_url(KEY, server.url())?));
+		Ok(())
+	}
+}
+
+impl Transport for Switchable {
+	fn get<'a>(&'a self, path: &'a str) -> TransportFuture<'a> {
+		let transport = self.0.lock().unwrap().clone().expect("no server");
+		Box::pin(async move { transport.get(path).await })
+	}
+}
+
+///Levels 4n up to 4n+3 are created at the same moment, to get ties on page boundaries
+fn level(n: u32) -> Level {
+	Level {
+		user_id: format!("user{:02}", n % 3),
+		tags: if n % 2 == 0 { vec!["ltag_short".into()] } else { vec![] },
+		stats: Some(LevelStats {
+			diamonds: (n % 6) as u8,
+			..Default::default()
+		}),
+		..fixture::level_on(n, n / 4 + 1)
+	}
+}
+
+async fn start(levels: impl IntoIterator<Item = Level>, players: impl IntoIterator<Item = Player>) -> Result<MockServer, Error> {
+	Ok(MockServer::start(common::dataset().levels(levels).players(players)).await?)
+}
+
+async fn server(levels: u32) -> Result<MockServer, Error> {
+	start((0..levels).map(level), []).await
+}
+
+fn codes(levels: &[Level]) -> Vec<&str> {
+	levels.iter().map(|level| level.level_id.as_str()).collect()
+}
+
+#[tokio::test]
+async fn polls() -> Result<(), Error> {
+	let transport = Switchable::default();
+	let client = RumpusClient::new(transport.clone());
+	//Small pages, so the new levels are spread over several of them
+	let mut watcher = LevelWatcher::new(client.clone(), LevelSearch::new().limit(3)?);
+	
+	//Halfway through a moment with 4 levels
+	let before = server(6).await?;
+	transport.switch(&before)?;
+	assert!(watcher.poll().await?.is_empty());
+	assert_eq!(watcher.cursor(), Some(&WatchCursor { created_at: "2022-02-02T12:00:00.000Z".into(), ids: vec!["level05".into(), "level04".into()] }));
+	assert!(watcher.poll().await?.is_empty());
+	
+	let after = server(13).await?;
+	transport.switch(&after)?;
+	//Including the ones created at the same moment as the newest level when it started
+	let new = watcher.poll().await?.into_iter().map(|new| new.level).collect::<Vec<_>>();
+	let mut found = codes(&new);
+	found.sort_unstable();
+	assert_eq!(found, ["code06", "code07", "code08", "code09", "code10", "code11", "code12"]);
+	//Oldest first
+	assert_eq!(new.last().unwrap().level_id, "code12");
+	assert!(watcher.poll().await?.is_empty());
+	
+	//Starting from scratch with filters
+	let mut watcher = LevelWatcher::new(client.clone(), LevelSearch::new().limit(3)?)
+		.since("2022-02-02T12:00:00.000Z")
+		.creators(["user00", "user01"])
+		.tags(["ltag_short"])
+		.min_diamonds(1);
+	let new = watcher.poll().await?.into_iter().map(|new| new.level).collect::<Vec<_>>();
+	let mut found = codes(&new);
+	found.sort_unstable();
+	//4, 6, 8, 10 and 12 are short, 8 is by user02, 6 and 12 have 0 diamonds
+	assert_eq!(found, ["code04", "code10"]);
+	
+	//Resuming where the first watcher stopped, as a stream
+	let cursor = WatchCursor { created_at: "2022-02-03T12:00:00.000Z".into(), ids: vec!["level08".into(), "level09".into()] };
+	let stream = LevelWatcher::new(client, LevelSearch::new().limit(3)?)
+		.resume(cursor)
+		.interval(Duration::from_millis(10))
+		.into_stream();
+	let new = stream.take(3).collect::<Vec<_>>().await.into_iter().collect::<Result<Vec<_>, _>>()?;
+	let mut found = new.iter().map(|new| new.level.level_id.as_str()).collect::<Vec<_>>();
+	found.sort_unstable();
+	assert_eq!(found, ["code10", "code11", "code12"]);
+	Ok(())
+}
+
+#[tokio::test]
+async fn creators_beyond_tower() -> Result<(), Error> {
+	let off_tower = Level {
+		tower: Some(false),
+		..level(4)
+	};
+	let server = start((0..4).map(level).chain([off_tower]), []).await?;
+	let client = RumpusClient::with_key_url(KEY, server.url())?;
+	
+	//Searching on the creators finds levels outside the tower as well
+	let mut watcher = LevelWatcher::new(client, LevelSearch::new())
+		.since("2022-02-01T12:00:00.000Z")
+		.creators(["user01"]);
+	let new = watcher.poll().await?.into_iter().map(|new| new.level).collect::<Vec<_>>();
+	assert_eq!(codes(&new), ["code01", "code04"]);
+	Ok(())
+}
+
+///Levels whose stats and records can be changed between polls
+fn tracked(n: u32, diamonds: u8, fastest_time: Vec<Record>) -> Level {
+	Level {
+		stats: Some(LevelStats { diamonds, ..Default::default() }),
+		records: Some(LevelRecords { fastest_time, ..Default::default() }),
+		..level(n)
+	}
+}
+
+#[tokio::test]
+async fn stats() -> Result<(), Error> {
+	let transport = Switchable::default();
+	//More levels than fit in a single request, and one that doesn't exist
+	let mut watcher = StatWatcher::new(RumpusClient::new(transport.clone()))
+		.track_levels((0..18).map(|n| format!("code{n:02}")))
+		.track_levels(["missing"])
+		.track_players(["user01"])
+		.subscriber_milestones([100, 10, 25]);
+	
+	let before = start(
+		(0..16).map(|n| tracked(n, 2, vec![]))
+			.chain([tracked(16, 3, vec![fixture::record("user01", 20.0)]), tracked(17, 6, vec![])]),
+		[fixture::player_with(1, 9, 1)],
+	).await?;
+	transport.switch(&before)?;
+	assert!(watcher.poll().await?.is_empty());
+	assert_eq!(watcher.level("code17").and_then(|level| level.stats.as_ref()).map(|stats| stats.diamonds), Some(6));
+	assert!(watcher.level("missing").is_none());
+	assert!(watcher.poll().await?.is_empty());
+	
+	let after = start(
+		(0..16).map(|n| tracked(n, 2, vec![]))
+			.chain([
+				tracked(16, 3, vec![fixture::record("user02", 15.0), fixture::record("user01", 20.0)]),
+				tracked(17, 4, vec![fixture::record("user02", 30.0)]),
+			]),
+		[fixture::player_with(1, 26, 4)],
+	).await?;
+	transport.switch(&after)?;
+	let events = watcher.poll().await?;
+	assert_eq!(events.len(), 5, "{events:?}");
+	for event in [
+		StatEvent::FastestTime { level_id: "code16".into(), record: fixture::record("user02", 15.0), previous: Some(Float(20.0)) },
+		StatEvent::FirstClear { level_id: "code17".into(), diamonds: 4 },
+		StatEvent::FastestTime { level_id: "code17".into(), record: fixture::record("user02", 30.0), previous: None },
+		StatEvent::SubscriberMilestone { user_id: "user01".into(), milestone: 25, subscribers: 26 },
+		StatEvent::CrownsGained { user_id: "user01".into(), gained: 3, crowns: 4 },
+	] {
+		assert!(events.contains(&event), "missing {event:?} in {events:?}");
+	}
+	assert_eq!(watcher.player("user01").map(|player| player.stats.subscribers), Some(26));
+	
+	//As a stream, continuing after a baseline
+	let mut watcher = StatWatcher::new(RumpusClient::new(transport.clone()))
+		.track_players(["user01"])
+		.interval(Duration::from_millis(10));
+	watcher.poll().await?;
+	let promoted = start([], [fixture::player_with(1, 1000, 4)]).await?;
+	transport.switch(&promoted)?;
+	let events = watcher.into_stream().take(1).collect::<Vec<_>>().await.into_iter().collect::<Result<Vec<_>, _>>()?;
+	assert_eq!(events, [StatEvent::SubscriberMilestone { user_id: "user01".into(), milestone: 1000, subscribers: 1000 }]);
+	Ok(())
+}